@@ -0,0 +1,7 @@
+-- judges excluded from the panel of a heat from a given wave on
+CREATE TABLE IF NOT EXISTS judge_exclusions (
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    judge_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    from_wave INTEGER NOT NULL,
+    PRIMARY KEY (heat_id, judge_id)
+);
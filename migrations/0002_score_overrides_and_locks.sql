@@ -0,0 +1,19 @@
+-- head judge overrides of a wave's computed score
+CREATE TABLE IF NOT EXISTS score_overrides (
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    surfer_id INTEGER NOT NULL REFERENCES surfers(id) ON DELETE CASCADE,
+    wave INTEGER NOT NULL,
+    score DOUBLE PRECISION,
+    voided BOOLEAN NOT NULL DEFAULT FALSE,
+    reason TEXT NOT NULL,
+    head_judge_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    override_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (heat_id, surfer_id, wave)
+);
+
+-- scores of a heat can not be changed by judges after locked_datetime
+CREATE TABLE IF NOT EXISTS heat_score_locks (
+    heat_id INTEGER PRIMARY KEY REFERENCES heats(id) ON DELETE CASCADE,
+    locked_datetime TIMESTAMP NOT NULL,
+    locked_by INTEGER REFERENCES users(id) ON DELETE SET NULL
+);
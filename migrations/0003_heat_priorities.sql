@@ -0,0 +1,7 @@
+-- priority order of surfers in a heat (lowest priority value has priority)
+CREATE TABLE IF NOT EXISTS heat_priorities (
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    surfer_id INTEGER NOT NULL REFERENCES surfers(id) ON DELETE CASCADE,
+    priority INTEGER NOT NULL,
+    PRIMARY KEY (heat_id, surfer_id)
+);
@@ -0,0 +1,9 @@
+-- waves ridden in a heat as logged by a spotter
+CREATE TABLE IF NOT EXISTS wave_log (
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    surfer_id INTEGER NOT NULL REFERENCES surfers(id) ON DELETE CASCADE,
+    wave INTEGER NOT NULL,
+    logged_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    spotter_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    PRIMARY KEY (heat_id, surfer_id, wave)
+);
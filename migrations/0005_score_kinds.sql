@@ -0,0 +1,2 @@
+-- kind of a scored ride (e.g. wave or jump) for multi-criteria judging
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS kind VARCHAR NOT NULL DEFAULT 'wave';
@@ -0,0 +1,15 @@
+-- teams of surfers competing in a tournament
+CREATE TABLE IF NOT EXISTS teams (
+    id SERIAL PRIMARY KEY,
+    tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS team_members (
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    surfer_id INTEGER NOT NULL REFERENCES surfers(id) ON DELETE CASCADE,
+    PRIMARY KEY (team_id, surfer_id)
+);
+
+-- categories with team scoring ('placing_points' or 'best_waves') count towards team standings
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS team_scoring VARCHAR NULL;
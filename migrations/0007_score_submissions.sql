@@ -0,0 +1,9 @@
+-- idempotency keys of applied score submissions of judging tablets
+CREATE TABLE IF NOT EXISTS score_submissions (
+    judge_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR NOT NULL,
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    client_datetime TIMESTAMP NOT NULL,
+    submitted_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (judge_id, idempotency_key)
+);
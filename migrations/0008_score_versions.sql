@@ -0,0 +1,2 @@
+-- version of a score, incremented on every update for optimistic concurrency
+ALTER TABLE scores ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,10 @@
+-- append-only log of what happened to a heat (the current state of a heat is derived from it)
+CREATE TABLE IF NOT EXISTS heat_events (
+    id SERIAL PRIMARY KEY,
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    event VARCHAR NOT NULL,
+    event_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS heat_events_heat_id_idx ON heat_events (heat_id);
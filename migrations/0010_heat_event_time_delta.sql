@@ -0,0 +1,2 @@
+-- seconds added to (or removed from) the heat time by an adjust_time event
+ALTER TABLE heat_events ADD COLUMN IF NOT EXISTS time_delta_s DOUBLE PRECISION;
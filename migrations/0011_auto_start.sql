@@ -0,0 +1,9 @@
+-- tournaments with auto start start armed heats at their (estimated) start time
+ALTER TABLE tournaments ADD COLUMN IF NOT EXISTS auto_start BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- heats released by an admin for being started automatically
+CREATE TABLE IF NOT EXISTS armed_heats (
+    heat_id INTEGER PRIMARY KEY REFERENCES heats(id) ON DELETE CASCADE,
+    armed_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    armed_by INTEGER REFERENCES users(id) ON DELETE SET NULL
+);
@@ -0,0 +1,8 @@
+-- tournaments on hold (e.g. lightning or no surf), heats can not be started while on hold
+CREATE TABLE IF NOT EXISTS tournament_holds (
+    tournament_id INTEGER PRIMARY KEY REFERENCES tournaments(id) ON DELETE CASCADE,
+    reason VARCHAR NOT NULL,
+    next_call_datetime TIMESTAMP,
+    hold_datetime TIMESTAMP NOT NULL DEFAULT NOW(),
+    held_by INTEGER REFERENCES users(id) ON DELETE SET NULL
+);
@@ -0,0 +1,16 @@
+-- competition areas (e.g. peaks of a beach) running heats in parallel
+CREATE TABLE IF NOT EXISTS areas (
+    id SERIAL PRIMARY KEY,
+    tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL
+);
+
+-- only one heat per area runs at a time (heats without area are not restricted)
+ALTER TABLE heats ADD COLUMN IF NOT EXISTS area_id INTEGER REFERENCES areas(id) ON DELETE SET NULL;
+
+-- judging panel of an area, assigned to the heats of the area when they start
+CREATE TABLE IF NOT EXISTS area_judges (
+    area_id INTEGER NOT NULL REFERENCES areas(id) ON DELETE CASCADE,
+    judge_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (area_id, judge_id)
+);
@@ -0,0 +1,20 @@
+-- instants are stored with time zone (as UTC), tournaments have an IANA time zone for display
+ALTER TABLE tournaments ADD COLUMN IF NOT EXISTS time_zone VARCHAR NOT NULL DEFAULT 'UTC';
+
+-- times entered by users or clients were given as UTC
+ALTER TABLE heats ALTER COLUMN start_datetime TYPE TIMESTAMPTZ USING start_datetime AT TIME ZONE 'UTC';
+ALTER TABLE score_submissions ALTER COLUMN client_datetime TYPE TIMESTAMPTZ USING client_datetime AT TIME ZONE 'UTC';
+ALTER TABLE tournament_holds ALTER COLUMN next_call_datetime TYPE TIMESTAMPTZ USING next_call_datetime AT TIME ZONE 'UTC';
+
+-- times set by NOW() are in the time zone of the database session
+ALTER TABLE IF EXISTS heat_state ALTER COLUMN start_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE IF EXISTS heat_state ALTER COLUMN end_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE IF EXISTS heat_state ALTER COLUMN pause_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE judging_requests ALTER COLUMN expire_date TYPE TIMESTAMPTZ;
+ALTER TABLE heat_score_locks ALTER COLUMN locked_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE score_overrides ALTER COLUMN override_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE wave_log ALTER COLUMN logged_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE score_submissions ALTER COLUMN submitted_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE heat_events ALTER COLUMN event_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE armed_heats ALTER COLUMN armed_datetime TYPE TIMESTAMPTZ;
+ALTER TABLE tournament_holds ALTER COLUMN hold_datetime TYPE TIMESTAMPTZ;
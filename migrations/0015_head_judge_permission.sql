@@ -0,0 +1,2 @@
+-- permissions are a database enum, head judges need their own value
+ALTER TYPE permissiontype ADD VALUE IF NOT EXISTS 'ac_headjudge';
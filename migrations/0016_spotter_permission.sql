@@ -0,0 +1,2 @@
+-- permissions are a database enum, spotters need their own value
+ALTER TYPE permissiontype ADD VALUE IF NOT EXISTS 'ac_spotter';
@@ -0,0 +1,2 @@
+-- heat types are a database enum, multi-criteria heats need their own value
+ALTER TYPE heattype ADD VALUE IF NOT EXISTS 'criteria';
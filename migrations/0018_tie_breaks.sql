@@ -0,0 +1,12 @@
+-- tie-break rule of the standard heats of a category (NULL uses the configured rule)
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS tie_break VARCHAR NULL;
+
+-- order of tied surfers decided by the head judge (lowest rank first)
+CREATE TABLE IF NOT EXISTS tie_decisions (
+    heat_id INTEGER NOT NULL REFERENCES heats(id) ON DELETE CASCADE,
+    surfer_id INTEGER NOT NULL REFERENCES surfers(id) ON DELETE CASCADE,
+    rank INTEGER NOT NULL,
+    head_judge_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    decision_datetime TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (heat_id, surfer_id)
+);
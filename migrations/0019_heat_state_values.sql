@@ -0,0 +1,3 @@
+-- heat states are a database enum, stopped heats are finished and heats not started yet are upcoming
+ALTER TYPE heatstatetype ADD VALUE IF NOT EXISTS 'finished';
+ALTER TYPE heatstatetype ADD VALUE IF NOT EXISTS 'upcoming';
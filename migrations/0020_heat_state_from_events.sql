@@ -0,0 +1,39 @@
+-- the current state of a heat is derived from its events, start and reset_time events carry the duration of the heat
+ALTER TABLE heat_events ADD COLUMN IF NOT EXISTS duration_m DOUBLE PRECISION;
+
+UPDATE heat_events e
+SET duration_m = h.duration
+FROM heats h
+WHERE e.heat_id = h.id AND e.event IN ('start', 'reset_time') AND e.duration_m IS NULL;
+
+-- heats started before the event log existed get the events leading to their state
+INSERT INTO heat_events (heat_id, event, event_datetime, duration_m)
+SELECT e.heat_id, e.event, e.event_datetime, e.duration_m
+FROM (
+  SELECT heat_id, 'start' AS event, start_datetime AS event_datetime,
+    CASE WHEN state = 'paused'
+      THEN (EXTRACT(EPOCH FROM pause_datetime - start_datetime) + remaining_time_s) / 60
+      ELSE EXTRACT(EPOCH FROM end_datetime - start_datetime) / 60 END AS duration_m
+  FROM heat_state
+  UNION ALL
+  SELECT heat_id, 'pause', pause_datetime, NULL FROM heat_state WHERE state = 'paused'
+  UNION ALL
+  SELECT heat_id, 'stop', end_datetime, NULL FROM heat_state WHERE state IN ('finished', 'inactive')
+) e
+WHERE NOT EXISTS (SELECT 1 FROM heat_events he WHERE he.heat_id = e.heat_id)
+ORDER BY e.heat_id, e.event_datetime;
+
+DROP TABLE IF EXISTS heat_state;
+
+-- state of each started heat after its last state changing event (start, pause, unpause or stop)
+-- times of a heat are computed by replaying all of its events
+CREATE OR REPLACE VIEW current_heat_states AS
+SELECT DISTINCT ON (heat_id)
+  heat_id,
+  (CASE event
+    WHEN 'pause' THEN 'paused'
+    WHEN 'stop' THEN 'finished'
+    ELSE 'active' END)::heatstatetype AS state
+FROM heat_events
+WHERE event IN ('start', 'pause', 'unpause', 'stop')
+ORDER BY heat_id, event_datetime DESC, id DESC;
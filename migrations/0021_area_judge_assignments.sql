@@ -0,0 +1,2 @@
+-- assignments of an area's panel are replaced when a heat of the area starts, other assignments are kept
+ALTER TABLE judge_assignments ADD COLUMN IF NOT EXISTS from_area BOOLEAN NOT NULL DEFAULT FALSE;
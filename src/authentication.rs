@@ -39,6 +39,14 @@ impl AuthenticatedUser {
     pub fn is_commentator(&self) -> bool {
         self.has_permission(&PermissionType::Commentator)
     }
+
+    pub fn is_head_judge(&self) -> bool {
+        self.has_permission(&PermissionType::HeadJudge)
+    }
+
+    pub fn is_spotter(&self) -> bool {
+        self.has_permission(&PermissionType::Spotter)
+    }
 }
 
 pub type Sessions = DashMap<String, AuthenticatedUser>;
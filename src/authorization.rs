@@ -1,5 +1,7 @@
 use crate::authentication::AuthenticatedUser;
+use crate::database::Pool;
 use crate::logging::LOG;
+use crate::models::judge::JudgingAssignment;
 
 use actix_web::{dev::Payload, error::ErrorUnauthorized, web, Error, FromRequest, HttpRequest};
 use anyhow::Result;
@@ -21,6 +23,8 @@ impl OsoState {
                 .add_method("is_admin", AuthenticatedUser::is_admin)
                 .add_method("is_judge", AuthenticatedUser::is_judge)
                 .add_method("is_commentator", AuthenticatedUser::is_commentator)
+                .add_method("is_head_judge", AuthenticatedUser::is_head_judge)
+                .add_method("is_spotter", AuthenticatedUser::is_spotter)
                 .build(),
         )?;
 
@@ -50,6 +54,19 @@ impl OsoState {
 #[derive(Serialize, Debug, Default, Clone)]
 pub struct AuthorizedUser(pub AuthenticatedUser);
 
+impl AuthorizedUser {
+    // admins and head judges assigned to a heat may manage all of its scores
+    pub async fn can_manage_heat(&self, db: &Pool, heat_id: u32) -> Result<bool> {
+        if self.0.is_admin() {
+            return Ok(true);
+        }
+        if !self.0.is_head_judge() {
+            return Ok(false);
+        }
+        JudgingAssignment::is_assigned(db, heat_id, self.0.id).await
+    }
+}
+
 impl fmt::Display for AuthorizedUser {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0.username)
@@ -1,3 +1,6 @@
+use crate::models::{result::TieBreak, score::ScoreKind};
+use crate::score_computation::Points;
+
 use config::{Config, Environment, File};
 use dotenv::dotenv;
 use once_cell::sync::Lazy;
@@ -32,6 +35,104 @@ pub struct Notifications {
     pub websocket_server_address: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Criterion {
+    pub kind: ScoreKind,
+    pub n_best: usize,
+    pub weight: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CallHeat {
+    pub points_per_wave_won: Points,
+    pub max_waves: Option<usize>,
+}
+
+impl Default for CallHeat {
+    fn default() -> Self {
+        CallHeat {
+            points_per_wave_won: Points::from_f64(1.0),
+            max_waves: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Judging {
+    pub quorum: Option<usize>,
+    pub score_lock_grace_s: f64,
+    pub criteria: Vec<Criterion>,
+    pub call: CallHeat,
+    pub tie_break: TieBreak,
+}
+
+impl Default for Judging {
+    fn default() -> Self {
+        Judging {
+            quorum: None,
+            score_lock_grace_s: 120.0,
+            criteria: vec![
+                Criterion {
+                    kind: ScoreKind::Wave,
+                    n_best: 2,
+                    weight: 1.0,
+                },
+                Criterion {
+                    kind: ScoreKind::Jump,
+                    n_best: 1,
+                    weight: 1.0,
+                },
+            ],
+            call: CallHeat::default(),
+            tie_break: TieBreak::RemainingWaves,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Teams {
+    // points for the places in a heat (first place first)
+    pub placing_points: Vec<Points>,
+    pub n_best_waves: usize,
+}
+
+impl Default for Teams {
+    fn default() -> Self {
+        Teams {
+            placing_points: [4.0, 3.0, 2.0, 1.0]
+                .iter()
+                .map(|p| Points::from_f64(*p))
+                .collect(),
+            n_best_waves: 2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HeatTimer {
+    pub enabled: bool,
+    pub interval_ms: u64,
+    // remaining times in seconds at which a signal is sent (0 is the end of the heat)
+    pub signals_s: Vec<f64>,
+    // finish heats automatically once their time is up
+    pub auto_stop: bool,
+}
+
+impl Default for HeatTimer {
+    fn default() -> Self {
+        HeatTimer {
+            enabled: true,
+            interval_ms: 500,
+            signals_s: vec![300.0, 60.0, 0.0],
+            auto_stop: true,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Settings {
     pub server_address: String,
@@ -41,6 +142,12 @@ pub struct Settings {
     pub api: API,
     pub auth: Auth,
     pub notifications: Notifications,
+    #[serde(default)]
+    pub judging: Judging,
+    #[serde(default)]
+    pub teams: Teams,
+    #[serde(default)]
+    pub heat_timer: HeatTimer,
 }
 
 pub static CONFIG: Lazy<Settings> = Lazy::new(|| {
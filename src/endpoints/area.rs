@@ -0,0 +1,171 @@
+use crate::authorization::AuthorizedUser;
+use crate::database::Pool;
+use crate::logging::LOG;
+use crate::models::{
+    area::{Area, AreaJudge, NewArea},
+    heat::Heat,
+};
+use crate::notifier::{Channel, Notifier};
+
+use actix_web::{error, web, Result};
+use serde::Deserialize;
+use serde_json::json;
+use slog::info;
+
+#[derive(Debug, Deserialize)]
+pub struct AreaSetting {
+    pub area_id: Option<u32>,
+}
+
+pub async fn get_by_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Option<Area>>> {
+    let area_id = path.into_inner();
+    let result = Area::find_by_id(db.get_ref(), area_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_by_tournament_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<Area>>> {
+    let tournament_id = path.into_inner();
+    let result = Area::find_by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_active_heats(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<Heat>>> {
+    let area_id = path.into_inner();
+    let result = Heat::find_active_heats_by_area_id(db.get_ref(), area_id, false)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn post(
+    path: web::Path<u32>,
+    web::Json(area): web::Json<NewArea>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Area>> {
+    let tournament_id = path.into_inner();
+    let result = Area::add(db.get_ref(), tournament_id, &area)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(
+        LOG,
+        "Added area {} to tournament {} by {}", result.id, tournament_id, user
+    );
+    Ok(web::Json(result))
+}
+
+pub async fn delete(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<Area>>> {
+    let area_id = path.into_inner();
+    let result = Area::delete(db.get_ref(), area_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    info!(LOG, "Deleted area {} by {}", area_id, user);
+    Ok(web::Json(result))
+}
+
+pub async fn put_heat_area(
+    path: web::Path<u32>,
+    web::Json(setting): web::Json<AreaSetting>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Option<Heat>>> {
+    let heat_id = path.into_inner();
+    let result = Heat::set_area(db.get_ref(), heat_id, setting.area_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if result.is_none() {
+        let heat = Heat::find_by_id(db.get_ref(), heat_id, false)
+            .await
+            .map_err(|e| {
+                error::ErrorInternalServerError(format!(
+                    "Error fetching data from database: {:?}",
+                    e
+                ))
+            })?;
+        return Err(match (heat, setting.area_id) {
+            (Some(_), Some(area_id)) => error::ErrorBadRequest(format!(
+                "Area {} is not an area of the tournament of heat {}",
+                area_id, heat_id
+            )),
+            _ => error::ErrorNotFound(format!("Heat {} not found", heat_id)),
+        });
+    }
+    notifier
+        .send(
+            Channel::Heats,
+            json!({
+                "heat_id": heat_id,
+                "msg": "area_changed"
+            }),
+        )
+        .unwrap();
+    Ok(web::Json(result))
+}
+
+pub async fn get_judges(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<AreaJudge>>> {
+    let area_id = path.into_inner();
+    let result = AreaJudge::find_by_area_id(db.get_ref(), area_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn add_judge(
+    path: web::Path<(u32, u32)>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<bool>> {
+    let (area_id, judge_id) = path.into_inner();
+    let result = AreaJudge::add(db.get_ref(), area_id, judge_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn delete_judge(
+    path: web::Path<(u32, u32)>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<bool>> {
+    let (area_id, judge_id) = path.into_inner();
+    let result = AreaJudge::delete(db.get_ref(), area_id, judge_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
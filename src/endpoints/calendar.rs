@@ -0,0 +1,353 @@
+use crate::database::Pool;
+use crate::models::{category::Category, heat::Heat, tournament::Tournament};
+
+use actix_web::{error, web, HttpResponse, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+// iCalendar feeds of heats (RFC 5545), calendar apps poll them to pick up schedule changes
+
+pub async fn get_by_tournament_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<HttpResponse> {
+    let tournament_id = path.into_inner();
+    let heats = Heat::find_by_tournament_id(db.get_ref(), tournament_id, true)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    let time_zone = tournament_time_zone(db.get_ref(), tournament_id).await?;
+    calendar_response(db.get_ref(), &heats, time_zone).await
+}
+
+pub async fn get_by_category_id(path: web::Path<u32>, db: web::Data<Pool>) -> Result<HttpResponse> {
+    let category_id = path.into_inner();
+    let heats = Heat::find_by_category_id(db.get_ref(), category_id, true)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    let category = Category::find_by_id(db.get_ref(), category_id, false)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    let time_zone = match category {
+        Some(category) => tournament_time_zone(db.get_ref(), category.tournament_id as u32).await?,
+        None => None,
+    };
+    calendar_response(db.get_ref(), &heats, time_zone).await
+}
+
+pub async fn get_by_surfer_id(path: web::Path<u32>, db: web::Data<Pool>) -> Result<HttpResponse> {
+    let surfer_id = path.into_inner();
+    let heats = Heat::find_by_surfer_id(db.get_ref(), surfer_id, true)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    // a surfer may have heats in tournaments with different time zones
+    calendar_response(db.get_ref(), &heats, None).await
+}
+
+async fn tournament_time_zone(db: &Pool, tournament_id: u32) -> Result<Option<String>> {
+    let tournament = Tournament::find_by_id(db, tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(tournament.map(|t| t.time_zone))
+}
+
+// times are given in UTC, the time zone tells calendar apps where the heats take place
+// without a given time zone, heats that all belong to one tournament use its time zone
+async fn calendar_response(
+    db: &Pool,
+    heats: &[Heat],
+    time_zone: Option<String>,
+) -> Result<HttpResponse> {
+    let mut categories = HashMap::<i32, Option<Category>>::new();
+    for heat in heats.iter() {
+        if let Entry::Vacant(entry) = categories.entry(heat.category_id) {
+            let category = Category::find_by_id(db, heat.category_id as u32, false)
+                .await
+                .map_err(|e| {
+                    error::ErrorInternalServerError(format!(
+                        "Error fetching data from database: {:?}",
+                        e
+                    ))
+                })?;
+            entry.insert(category);
+        }
+    }
+    let time_zone = match time_zone {
+        Some(time_zone) => Some(time_zone),
+        None => {
+            let tournament_ids: HashSet<i32> = categories
+                .values()
+                .flatten()
+                .map(|c| c.tournament_id)
+                .collect();
+            match tournament_ids.iter().next() {
+                Some(&tournament_id) if tournament_ids.len() == 1 => {
+                    tournament_time_zone(db, tournament_id as u32).await?
+                }
+                _ => None,
+            }
+        }
+    };
+    let calendar = heat_calendar(heats, &categories, time_zone, Utc::now());
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(calendar))
+}
+
+fn heat_calendar(
+    heats: &[Heat],
+    categories: &HashMap<i32, Option<Category>>,
+    time_zone: Option<String>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//surfjudge//heats//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        // ask calendar apps to poll for changed heat times
+        "REFRESH-INTERVAL;VALUE=DURATION:PT15M".to_string(),
+        "X-PUBLISHED-TTL:PT15M".to_string(),
+    ];
+    if let Some(time_zone) = time_zone {
+        lines.push(format!("X-WR-TIMEZONE:{}", time_zone));
+    }
+    for heat in heats.iter() {
+        let category_name = categories
+            .get(&heat.category_id)
+            .and_then(|c| c.as_ref())
+            .map(|c| c.name.as_str())
+            .unwrap_or("");
+        let participants: Vec<String> = heat
+            .participations
+            .iter()
+            .flatten()
+            .map(|p| {
+                let name = p
+                    .surfer
+                    .as_ref()
+                    .map(|s| format!("{} {}", s.first_name, s.last_name))
+                    .unwrap_or_else(|| format!("Surfer {}", p.surfer_id));
+                match &p.lycra_color {
+                    Some(color) => format!("{} ({})", name, color.name),
+                    None => name,
+                }
+            })
+            .collect();
+        let end = heat.start_datetime + Duration::milliseconds((heat.duration * 60000.0) as i64);
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:heat-{}@surfjudge", heat.id));
+        lines.push(format!("DTSTAMP:{}", ical_datetime(now)));
+        lines.push(format!("DTSTART:{}", ical_datetime(heat.start_datetime)));
+        lines.push(format!("DTEND:{}", ical_datetime(end)));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("{} - {}", category_name, heat.name))
+        ));
+        if !participants.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_text(&participants.join("\n"))
+            ));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|l| fold_line(l))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+fn ical_datetime(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// lines longer than 75 octets are continued on lines starting with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        heat::HeatType, lycra_color::LycraColor, participation::Participation, surfer::Surfer,
+    };
+    use chrono::TimeZone;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 8, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn heat(name: &str, participations: Option<Vec<Participation>>) -> Heat {
+        Heat {
+            id: 1,
+            category_id: 1,
+            name: name.to_string(),
+            round: 0,
+            number_in_round: 0,
+            start_datetime: at(0),
+            number_of_waves: 10,
+            duration: 20.0,
+            heat_type: HeatType::Standard,
+            additional_info: None,
+            area_id: None,
+            participations,
+        }
+    }
+
+    fn categories(name: &str) -> HashMap<i32, Option<Category>> {
+        let category = Category {
+            id: 1,
+            tournament_id: 1,
+            name: name.to_string(),
+            additional_info: None,
+            team_scoring: None,
+            tie_break: None,
+            tournament: None,
+        };
+        vec![(1, Some(category))].into_iter().collect()
+    }
+
+    fn participation(surfer_id: i32, first_name: &str, color: Option<&str>) -> Participation {
+        Participation {
+            surfer_id,
+            heat_id: 1,
+            lycra_color_id: 1,
+            seed: surfer_id,
+            surfer: Some(Surfer {
+                id: surfer_id,
+                first_name: first_name.to_string(),
+                last_name: "Doe".to_string(),
+                country: None,
+                additional_info: None,
+            }),
+            lycra_color: color.map(|c| LycraColor {
+                id: 1,
+                seed: 0,
+                name: c.to_string(),
+                hex: "ff0000".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("Open, Men; Final"), "Open\\, Men\\; Final");
+        assert_eq!(escape_text("a\\b"), "a\\\\b");
+        assert_eq!(escape_text("first\nsecond"), "first\\nsecond");
+        // backslashes added for escaping are not escaped again
+        assert_eq!(escape_text("\\,"), "\\\\\\,");
+    }
+
+    #[test]
+    fn test_fold_short_line() {
+        let line = "a".repeat(75);
+        assert_eq!(fold_line(&line), line);
+    }
+
+    #[test]
+    fn test_fold_long_line() {
+        let folded = fold_line(&"a".repeat(160));
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        // continuation lines start with a space counting towards their 75 octets
+        assert_eq!(
+            lines.iter().map(|l| l.len()).collect::<Vec<usize>>(),
+            vec![75, 75, 12]
+        );
+        assert!(lines[1..].iter().all(|l| l.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), "a".repeat(160));
+    }
+
+    #[test]
+    fn test_fold_multibyte() {
+        // 74 octets and a two octet character do not fit into one line
+        let line = format!("{}é", "a".repeat(74));
+        assert_eq!(fold_line(&line), format!("{}\r\n é", "a".repeat(74)));
+
+        // 73 octets and a two octet character make exactly 75
+        let line = format!("{}é", "a".repeat(73));
+        assert_eq!(fold_line(&line), line);
+
+        // characters are never split
+        let folded = fold_line(&"ü".repeat(80));
+        for l in folded.split("\r\n") {
+            assert!(l.len() <= 75);
+        }
+        assert_eq!(folded.split("\r\n").next().unwrap().len(), 74);
+        assert_eq!(folded.replace("\r\n ", ""), "ü".repeat(80));
+    }
+
+    #[test]
+    fn test_heat_calendar() {
+        let heats = vec![heat(
+            "Heat 1",
+            Some(vec![
+                participation(1, "Jane", Some("red")),
+                participation(2, "John", None),
+            ]),
+        )];
+        let calendar = heat_calendar(
+            &heats,
+            &categories("Open, Women"),
+            Some("Europe/Lisbon".to_string()),
+            at(-60),
+        );
+        let lines: Vec<&str> = calendar.split("\r\n").collect();
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines.contains(&"X-WR-TIMEZONE:Europe/Lisbon"));
+        assert!(lines.contains(&"UID:heat-1@surfjudge"));
+        assert!(lines.contains(&"DTSTAMP:20260601T070000Z"));
+        assert!(lines.contains(&"DTSTART:20260601T080000Z"));
+        assert!(lines.contains(&"DTEND:20260601T082000Z"));
+        assert!(lines.contains(&"SUMMARY:Open\\, Women - Heat 1"));
+        assert!(lines.contains(&"DESCRIPTION:Jane Doe (red)\\nJohn Doe"));
+        assert_eq!(lines[lines.len() - 2], "END:VCALENDAR");
+        assert_eq!(lines[lines.len() - 1], "");
+    }
+
+    #[test]
+    fn test_heat_calendar_folds_multibyte_names() {
+        let name = "Ébauche ".repeat(12);
+        let heats = vec![heat(&name, None)];
+        let calendar = heat_calendar(&heats, &categories("Catégorie"), None, at(0));
+        assert!(!calendar.contains("X-WR-TIMEZONE"));
+        assert!(!calendar.contains("DESCRIPTION"));
+        for line in calendar.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        let unfolded = calendar.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:Catégorie - {}", name)));
+    }
+}
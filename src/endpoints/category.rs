@@ -1,7 +1,19 @@
+use crate::authorization::AuthorizedUser;
 use crate::database::Pool;
-use crate::models::category::Category;
+use crate::models::{category::Category, result::TieBreak, team::TeamScoring};
 
 use actix_web::{error, web, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct TeamScoringSetting {
+    pub team_scoring: Option<TeamScoring>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TieBreakSetting {
+    pub tie_break: Option<TieBreak>,
+}
 
 pub async fn get_all(db: web::Data<Pool>) -> Result<web::Json<Vec<Category>>> {
     let result = Category::find_all(db.get_ref(), false).await.map_err(|e| {
@@ -35,3 +47,33 @@ pub async fn get_by_tournament_id(
         })?;
     Ok(web::Json(result))
 }
+
+pub async fn put_team_scoring(
+    path: web::Path<u32>,
+    web::Json(setting): web::Json<TeamScoringSetting>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Option<Category>>> {
+    let category_id = path.into_inner();
+    let result = Category::set_team_scoring(db.get_ref(), category_id, setting.team_scoring)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn put_tie_break(
+    path: web::Path<u32>,
+    web::Json(setting): web::Json<TieBreakSetting>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Option<Category>>> {
+    let category_id = path.into_inner();
+    let result = Category::set_tie_break(db.get_ref(), category_id, setting.tie_break)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
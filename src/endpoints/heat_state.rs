@@ -1,22 +1,37 @@
 use crate::authorization::AuthorizedUser;
+use crate::configuration::CONFIG;
 use crate::database::Pool;
+use crate::endpoints::schedule::notify_schedule;
 use crate::logging::LOG;
 use crate::models::{
+    armed_heat::ArmedHeat,
     heat::Heat,
+    heat_event::HeatTimeline,
     heat_state::{HeatState, HeatStateType},
+    score_lock::ScoreLock,
+    tournament_hold::TournamentHold,
 };
 use crate::notifier::{Channel, Notifier};
 
 use actix_web::{error, web, Result};
-use chrono::Utc;
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use slog::info;
+use slog::{info, warn};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ResultHeatState {
     pub state: HeatStateType,
     pub remaining_time_s: f64,
+    // absolute end of a running heat, clients should count down to this (see public time endpoint)
+    pub end_datetime: Option<DateTime<Utc>>,
+    pub server_datetime: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeatTimeAdjustment {
+    // negative to shorten the heat
+    pub seconds: f64,
 }
 
 pub async fn get_by_heat_id(
@@ -30,13 +45,18 @@ pub async fn get_by_heat_id(
             error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
         })?;
 
-    let state = match &result {
-        Some(heat_state) => heat_state.state.clone(),
-        None => HeatStateType::Inactive,
-    };
+    let state = HeatStateType::of(result.as_ref());
     let remaining_time_s = match &result {
-        // if the heat is not active, there is no heat state row -> take duration from heat data
-        None => Heat::find_by_id(db.get_ref(), heat_id, false)
+        Some(heat_state) if heat_state.state == HeatStateType::Paused => {
+            heat_state.remaining_time_s.unwrap_or(0.0).max(0.0)
+        }
+        Some(heat_state) if heat_state.state == HeatStateType::Active => {
+            let now = Utc::now();
+            let diff = (heat_state.end_datetime - now).num_milliseconds() as f64 / 1000.0;
+            diff.max(0.0)
+        }
+        // heats that are not running (upcoming or finished) show their full duration
+        _ => Heat::find_by_id(db.get_ref(), heat_id, false)
             .await
             .map_err(|e| {
                 error::ErrorInternalServerError(format!(
@@ -46,22 +66,34 @@ pub async fn get_by_heat_id(
             })?
             .map(|h| h.duration * 60.0)
             .unwrap_or(0.0),
-        Some(heat_state) => match heat_state.state {
-            HeatStateType::Paused => heat_state.remaining_time_s.unwrap_or(0.0).max(0.0),
-            HeatStateType::Active => {
-                let now = Utc::now().naive_utc();
-                let diff = (heat_state.end_datetime - now).num_milliseconds() as f64 / 1000.0;
-                diff.max(0.0)
-            }
-            _ => 0.0,
-        },
     };
+    let end_datetime = result
+        .as_ref()
+        .and_then(|heat_state| match heat_state.state {
+            HeatStateType::Active | HeatStateType::Finished => Some(heat_state.end_datetime),
+            _ => None,
+        });
     Ok(web::Json(ResultHeatState {
         state,
         remaining_time_s,
+        end_datetime,
+        server_datetime: Utc::now(),
     }))
 }
 
+pub async fn get_timeline_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<HeatTimeline>> {
+    let result = HeatTimeline::by_heat_id(db.get_ref(), path.into_inner())
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
 pub async fn start_heat(
     path: web::Path<u32>,
     db: web::Data<Pool>,
@@ -69,14 +101,73 @@ pub async fn start_heat(
     user: AuthorizedUser,
 ) -> Result<web::Json<&'static str>> {
     let heat_id = path.into_inner();
-    HeatState::set_heat_started(&db, heat_id)
+    let started = HeatState::set_heat_started(&db, heat_id, Some(user.0.id))
         .await
         .map_err(|e| {
-            // TODO: not found error in case it was not found
             error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
         })?;
+    if !started {
+        check_not_on_hold(db.get_ref(), heat_id).await?;
+        check_area_free(db.get_ref(), heat_id).await?;
+        let heat = Heat::find_by_id(db.get_ref(), heat_id, false)
+            .await
+            .map_err(|e| {
+                error::ErrorInternalServerError(format!(
+                    "Error fetching data from database: {:?}",
+                    e
+                ))
+            })?;
+        return Err(match heat {
+            Some(_) => error::ErrorConflict(format!("Heat {} is already running", heat_id)),
+            None => error::ErrorNotFound(format!("Heat {} not found", heat_id)),
+        });
+    }
 
     info!(LOG, "Start heat {} by {}", heat_id, user);
+    notify_heat_started(db.get_ref(), &notifier, heat_id).await;
+    Ok(web::Json("Started heat!"))
+}
+
+// refuse starting a heat while another heat is running in its area
+async fn check_area_free(db: &Pool, heat_id: u32) -> Result<()> {
+    let heat = Heat::find_by_id(db, heat_id, false).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    let area_id = match heat.and_then(|h| h.area_id) {
+        Some(area_id) => area_id,
+        None => return Ok(()),
+    };
+    let active_heats = Heat::find_active_heats_by_area_id(db, area_id as u32, false)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    match active_heats.iter().find(|h| h.id != heat_id as i32) {
+        Some(running) => Err(error::ErrorConflict(format!(
+            "Heat {} is running in the same area",
+            running.id
+        ))),
+        None => Ok(()),
+    }
+}
+
+async fn check_not_on_hold(db: &Pool, heat_id: u32) -> Result<()> {
+    let hold = TournamentHold::find_by_heat_id(db, heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    match hold {
+        Some(hold) => Err(error::ErrorConflict(format!(
+            "Tournament is on hold: {}",
+            hold.reason
+        ))),
+        None => Ok(()),
+    }
+}
+
+// also used for heats started automatically
+pub async fn notify_heat_started(db: &Pool, notifier: &Notifier, heat_id: u32) {
     notifier
         .send(
             Channel::ActiveHeats,
@@ -86,7 +177,20 @@ pub async fn start_heat(
             }),
         )
         .unwrap();
-    Ok(web::Json("Started heat!"))
+    notifier
+        .send(
+            Channel::Priority,
+            json!({
+                "heat_id": heat_id,
+                "priority": []
+            }),
+        )
+        .unwrap();
+    // the panel of the heat's area is assigned on start
+    notifier
+        .send(Channel::JudgingAssignments, json!("changed"))
+        .unwrap();
+    notify_schedule(db, notifier, heat_id).await;
 }
 
 pub async fn stop_heat(
@@ -96,23 +200,58 @@ pub async fn stop_heat(
     user: AuthorizedUser,
 ) -> Result<web::Json<&'static str>> {
     let heat_id = path.into_inner();
-    HeatState::set_heat_stopped(&db, heat_id)
-        .await
-        .map_err(|e| {
-            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
-        })?;
+    HeatState::set_heat_stopped(
+        &db,
+        heat_id,
+        Some(user.0.id),
+        CONFIG.judging.score_lock_grace_s,
+    )
+    .await
+    .map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
 
     info!(LOG, "Stop heat {} by {}", heat_id, user);
+    notify_heat_stopped(db.get_ref(), &notifier, heat_id).await;
+    Ok(web::Json("Stopped heat!"))
+}
+
+// also used for heats finished by the heat timer
+pub async fn notify_heat_stopped(db: &Pool, notifier: &Notifier, heat_id: u32) {
+    // scores get locked after a grace period, judges are told when
+    let score_lock = ScoreLock::find_by_heat_id(db, heat_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                LOG,
+                "Could not fetch score lock of heat {}: {:?}", heat_id, e
+            );
+            None
+        });
+    let locked_datetime = score_lock.map(|l| l.locked_datetime);
     notifier
         .send(
             Channel::ActiveHeats,
             json!({
                 "heat_id": heat_id,
-                "msg": "stop_heat"
+                "msg": "stop_heat",
+                "score_lock_datetime": locked_datetime
             }),
         )
         .unwrap();
-    Ok(web::Json("Stopped heat!"))
+    if let Some(locked_datetime) = locked_datetime {
+        notifier
+            .send(
+                Channel::Scores,
+                json!({
+                    "heat_id": heat_id,
+                    "msg": "lock_scores",
+                    "locked_datetime": locked_datetime
+                }),
+            )
+            .unwrap();
+    }
+    notify_schedule(db, notifier, heat_id).await;
 }
 
 pub async fn toggle_heat_pause(
@@ -122,7 +261,9 @@ pub async fn toggle_heat_pause(
     user: AuthorizedUser,
 ) -> Result<web::Json<&'static str>> {
     let heat_id = path.into_inner();
-    HeatState::toggle_heat_pause(&db, heat_id)
+    // heats of a tournament on hold are paused and stay paused
+    check_not_on_hold(db.get_ref(), heat_id).await?;
+    HeatState::toggle_heat_pause(&db, heat_id, Some(user.0.id))
         .await
         .map_err(|e| {
             error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
@@ -138,6 +279,7 @@ pub async fn toggle_heat_pause(
             }),
         )
         .unwrap();
+    notify_schedule(db.get_ref(), &notifier, heat_id).await;
     Ok(web::Json("Toggled heat pause!"))
 }
 
@@ -148,7 +290,7 @@ pub async fn reset_heat_time(
     user: AuthorizedUser,
 ) -> Result<web::Json<&'static str>> {
     let heat_id = path.into_inner();
-    HeatState::reset_heat_time(&db, heat_id)
+    HeatState::reset_heat_time(&db, heat_id, Some(user.0.id))
         .await
         .map_err(|e| {
             error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
@@ -164,5 +306,74 @@ pub async fn reset_heat_time(
             }),
         )
         .unwrap();
+    notify_schedule(db.get_ref(), &notifier, heat_id).await;
     Ok(web::Json("Reset heat time!"))
 }
+
+pub async fn adjust_heat_time(
+    path: web::Path<u32>,
+    adjustment: web::Json<HeatTimeAdjustment>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<&'static str>> {
+    let heat_id = path.into_inner();
+    let adjusted = HeatState::adjust_heat_time(&db, heat_id, adjustment.seconds, Some(user.0.id))
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if !adjusted {
+        return Err(error::ErrorBadRequest(format!(
+            "Heat {} is not running or paused",
+            heat_id
+        )));
+    }
+
+    info!(
+        LOG,
+        "Adjust heat time for heat {} by {}s by {}", heat_id, adjustment.seconds, user
+    );
+    notifier
+        .send(
+            Channel::ActiveHeats,
+            json!({
+                "heat_id": heat_id,
+                "msg": "adjust_heat_time",
+                "seconds": adjustment.seconds
+            }),
+        )
+        .unwrap();
+    notify_schedule(db.get_ref(), &notifier, heat_id).await;
+    Ok(web::Json("Adjusted heat time!"))
+}
+
+pub async fn arm_heat(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<ArmedHeat>>> {
+    let heat_id = path.into_inner();
+    let result = ArmedHeat::arm(db.get_ref(), heat_id, user.0.id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(LOG, "Arm heat {} by {}", heat_id, user);
+    Ok(web::Json(result))
+}
+
+pub async fn disarm_heat(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<bool>> {
+    let heat_id = path.into_inner();
+    let result = ArmedHeat::disarm(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(LOG, "Disarm heat {} by {}", heat_id, user);
+    Ok(web::Json(result))
+}
@@ -1,14 +1,22 @@
 use crate::authorization::AuthorizedUser;
 use crate::database::Pool;
 use crate::models::heat::Heat;
-use crate::models::judge::{JudgingAssignment, JudgingRequest};
+use crate::models::judge::{JudgeExclusion, JudgingAssignment, JudgingRequest};
+use crate::models::judge_analytics::JudgeAnalytics;
 use crate::models::permission::PermissionType;
 use crate::models::user::User;
 use crate::notifier::{Channel, Notifier};
+use crate::score_computation::judge_analytics::JudgeStatistics;
 
 use actix_web::{error, web, Result};
+use serde::Deserialize;
 use serde_json::json;
 
+#[derive(Debug, Deserialize)]
+pub struct Exclusion {
+    pub from_wave: i32,
+}
+
 pub async fn get_all(db: web::Data<Pool>, _: AuthorizedUser) -> Result<web::Json<Vec<User>>> {
     let result = User::find_by_permission(db.get_ref(), PermissionType::Judge, false)
         .await
@@ -86,6 +94,118 @@ pub async fn delete_assignment(
     Ok(web::Json("Judging assignment deleted!"))
 }
 
+pub async fn get_exclusions_for_heat(
+    db: web::Data<Pool>,
+    path: web::Path<u32>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<JudgeExclusion>>> {
+    let heat_id = path.into_inner();
+    let result = JudgeExclusion::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn add_exclusion(
+    path: web::Path<(u32, u32)>,
+    web::Json(exclusion): web::Json<Exclusion>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<&'static str>> {
+    let (heat_id, judge_id) = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+    if exclusion.from_wave < 0 {
+        return Err(error::ErrorBadRequest(format!(
+            "Invalid wave {}",
+            exclusion.from_wave
+        )));
+    }
+    let assigned = JudgingAssignment::is_assigned(db.get_ref(), heat_id, judge_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if !assigned {
+        return Err(error::ErrorBadRequest(format!(
+            "Judge {} is not assigned to heat {}",
+            judge_id, heat_id
+        )));
+    }
+    JudgeExclusion::add(db.get_ref(), heat_id, judge_id, exclusion.from_wave)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notifier
+        .send(Channel::JudgingAssignments, json!("changed"))
+        .unwrap();
+    Ok(web::Json("Judge exclusion added!"))
+}
+
+pub async fn delete_exclusion(
+    path: web::Path<(u32, u32)>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<&'static str>> {
+    let (heat_id, judge_id) = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+    JudgeExclusion::delete(db.get_ref(), heat_id, judge_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notifier
+        .send(Channel::JudgingAssignments, json!("changed"))
+        .unwrap();
+    Ok(web::Json("Judge exclusion deleted!"))
+}
+
+pub async fn get_analytics_by_heat_id(
+    db: web::Data<Pool>,
+    path: web::Path<u32>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<JudgeStatistics>>> {
+    let heat_id = path.into_inner();
+    let result = JudgeAnalytics::by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error computing judge analytics: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_analytics_by_category_id(
+    db: web::Data<Pool>,
+    path: web::Path<u32>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<JudgeStatistics>>> {
+    let category_id = path.into_inner();
+    let result = JudgeAnalytics::by_category_id(db.get_ref(), category_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error computing judge analytics: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_analytics_by_tournament_id(
+    db: web::Data<Pool>,
+    path: web::Path<u32>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<JudgeStatistics>>> {
+    let tournament_id = path.into_inner();
+    let result = JudgeAnalytics::by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error computing judge analytics: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
 pub async fn get_requests(
     db: web::Data<Pool>,
     _: AuthorizedUser,
@@ -121,3 +241,16 @@ pub async fn add_request(
         .unwrap();
     Ok(web::Json("Judging request received!"))
 }
+
+async fn check_can_manage_heat(db: &Pool, user: &AuthorizedUser, heat_id: u32) -> Result<()> {
+    let can_manage_heat = user.can_manage_heat(db, heat_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    if !can_manage_heat {
+        return Err(error::ErrorForbidden(format!(
+            "User '{}' not allowed to manage judges of heat '{}'",
+            user.0.username, heat_id
+        )));
+    }
+    Ok(())
+}
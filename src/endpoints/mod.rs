@@ -1,5 +1,7 @@
 pub mod config;
 
+pub mod area;
+pub mod calendar;
 pub mod category;
 pub mod heat;
 pub mod heat_advancement;
@@ -7,10 +9,15 @@ pub mod heat_state;
 pub mod judge;
 pub mod lycra_color;
 pub mod participation;
+pub mod priority;
 pub mod result;
+pub mod schedule;
 pub mod score;
 pub mod surfer;
+pub mod team;
+pub mod time;
 pub mod tournament;
+pub mod wave_log;
 
 pub mod pages;
 
@@ -0,0 +1,121 @@
+use crate::authorization::AuthorizedUser;
+use crate::database::Pool;
+use crate::logging::LOG;
+use crate::models::{participation::Participation, priority::Priority};
+use crate::notifier::{Channel, Notifier};
+
+use actix_web::{error, web, Result};
+use serde_json::json;
+use slog::{info, warn};
+use std::collections::HashSet;
+
+pub async fn get_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<Priority>>> {
+    let heat_id = path.into_inner();
+    let result = Priority::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn put(
+    path: web::Path<u32>,
+    web::Json(surfer_ids): web::Json<Vec<i32>>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Vec<Priority>>> {
+    let heat_id = path.into_inner();
+    check_surfer_ids(db.get_ref(), heat_id, &surfer_ids).await?;
+    let result = Priority::set(db.get_ref(), heat_id, &surfer_ids)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    info!(LOG, "Set priority for heat {} by {}", heat_id, user);
+    notifier
+        .send(
+            Channel::Priority,
+            json!({
+                "heat_id": heat_id,
+                "priority": result
+            }),
+        )
+        .unwrap();
+    Ok(web::Json(result))
+}
+
+// the priority order lists surfers of the heat, each at most once
+async fn check_surfer_ids(db: &Pool, heat_id: u32, surfer_ids: &[i32]) -> Result<()> {
+    let participants: HashSet<i32> = Participation::find_by_heat_id(db, heat_id, false)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?
+        .iter()
+        .map(|p| p.surfer_id)
+        .collect();
+    let mut seen = HashSet::new();
+    for surfer_id in surfer_ids.iter() {
+        if !participants.contains(surfer_id) {
+            return Err(error::ErrorBadRequest(format!(
+                "Surfer {} does not participate in heat {}",
+                surfer_id, heat_id
+            )));
+        }
+        if !seen.insert(surfer_id) {
+            return Err(error::ErrorBadRequest(format!(
+                "Surfer {} is listed more than once",
+                surfer_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+// move a surfer that rode a wave to the end of the priority order and notify clients
+pub async fn surfer_rode_wave(db: &Pool, notifier: &Notifier, heat_id: u32, surfer_id: u32) {
+    if let Err(e) = Priority::surfer_rode_wave(db, heat_id, surfer_id).await {
+        warn!(
+            LOG,
+            "Could not update priority for heat {}: {:?}", heat_id, e
+        );
+        return;
+    }
+    notify_priority(db, notifier, heat_id).await;
+}
+
+// undo the priority change of a deleted wave and notify clients
+pub async fn wave_deleted(db: &Pool, notifier: &Notifier, heat_id: u32) {
+    if let Err(e) = Priority::from_wave_log(db, heat_id).await {
+        warn!(
+            LOG,
+            "Could not update priority for heat {}: {:?}", heat_id, e
+        );
+        return;
+    }
+    notify_priority(db, notifier, heat_id).await;
+}
+
+async fn notify_priority(db: &Pool, notifier: &Notifier, heat_id: u32) {
+    match Priority::find_by_heat_id(db, heat_id).await {
+        Ok(priority) => notifier
+            .send(
+                Channel::Priority,
+                json!({
+                    "heat_id": heat_id,
+                    "priority": priority
+                }),
+            )
+            .unwrap(),
+        Err(e) => warn!(
+            LOG,
+            "Could not fetch priority for heat {}: {:?}", heat_id, e
+        ),
+    }
+}
@@ -1,7 +1,12 @@
+use crate::models::needs::Needs;
 use crate::models::preliminary_result::PreliminaryResult;
 use crate::models::result::Result;
+use crate::models::tie_decision::TieDecision;
+use crate::notifier::{Channel, Notifier};
+use crate::score_computation::needs::SurferNeeds;
 use crate::{authorization::AuthorizedUser, database::Pool};
 use actix_web::{error, web};
+use serde_json::json;
 
 pub async fn get_all(db: web::Data<Pool>) -> actix_web::Result<web::Json<Vec<Result>>> {
     let result = Result::find_all(db.get_ref(), true).await.map_err(|e| {
@@ -39,9 +44,21 @@ pub async fn get_by_category_id(
 pub async fn get_preliminary_by_heat_id(
     path: web::Path<u32>,
     db: web::Data<Pool>,
-    _user: AuthorizedUser,
+    user: AuthorizedUser,
 ) -> actix_web::Result<web::Json<Vec<Result>>> {
     let heat_id = path.into_inner();
+    let can_manage_heat = user
+        .can_manage_heat(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if !can_manage_heat {
+        return Err(error::ErrorForbidden(format!(
+            "User '{}' not allowed to view preliminary results of heat '{}'",
+            user.0.username, heat_id
+        )));
+    }
     let results = PreliminaryResult::by_heat_id(db.get_ref(), heat_id)
         .await
         .map_err(|e| {
@@ -49,3 +66,94 @@ pub async fn get_preliminary_by_heat_id(
         })?;
     Ok(web::Json(results))
 }
+
+pub async fn get_needs_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> actix_web::Result<web::Json<Vec<SurferNeeds>>> {
+    let heat_id = path.into_inner();
+    let needs = Needs::by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| error::ErrorInternalServerError(format!("Error computing needs: {:?}", e)))?;
+    Ok(web::Json(needs))
+}
+
+pub async fn get_tie_decisions_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> actix_web::Result<web::Json<Vec<TieDecision>>> {
+    let heat_id = path.into_inner();
+    check_can_decide_ties(db.get_ref(), &user, heat_id).await?;
+    let result = TieDecision::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+// order of tied surfers decided by the head judge (first surfer ranked highest)
+pub async fn put_tie_decision(
+    path: web::Path<u32>,
+    web::Json(surfer_ids): web::Json<Vec<i32>>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> actix_web::Result<web::Json<Vec<TieDecision>>> {
+    let heat_id = path.into_inner();
+    check_can_decide_ties(db.get_ref(), &user, heat_id).await?;
+    let result = TieDecision::set(db.get_ref(), heat_id, user.0.id, &surfer_ids)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notify_tie_decision(&notifier, heat_id);
+    Ok(web::Json(result))
+}
+
+pub async fn delete_tie_decision(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> actix_web::Result<web::Json<bool>> {
+    let heat_id = path.into_inner();
+    check_can_decide_ties(db.get_ref(), &user, heat_id).await?;
+    let result = TieDecision::delete(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notify_tie_decision(&notifier, heat_id);
+    Ok(web::Json(result))
+}
+
+fn notify_tie_decision(notifier: &Notifier, heat_id: u32) {
+    notifier
+        .send(
+            Channel::Results,
+            json!({
+                "heat_id": heat_id,
+                "msg": "tie_decision"
+            }),
+        )
+        .unwrap();
+}
+
+async fn check_can_decide_ties(
+    db: &Pool,
+    user: &AuthorizedUser,
+    heat_id: u32,
+) -> actix_web::Result<()> {
+    let can_manage_heat = user.can_manage_heat(db, heat_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    if !can_manage_heat {
+        return Err(error::ErrorForbidden(format!(
+            "User '{}' not allowed to decide ties of heat '{}'",
+            user.0.username, heat_id
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::database::Pool;
+use crate::logging::LOG;
+use crate::models::schedule::{Schedule, ScheduledHeat};
+use crate::notifier::{Channel, Notifier};
+
+use actix_web::{error, web, Result};
+use serde_json::json;
+use slog::warn;
+
+pub async fn get_by_tournament_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<ScheduledHeat>>> {
+    let tournament_id = path.into_inner();
+    let result = Schedule::by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+// send the updated schedule of the tournament of a heat whose times changed
+pub async fn notify_schedule(db: &Pool, notifier: &Notifier, heat_id: u32) {
+    match Schedule::tournament_id_by_heat_id(db, heat_id).await {
+        Ok(Some(tournament_id)) => {
+            notify_tournament_schedule(db, notifier, tournament_id as u32).await
+        }
+        Ok(None) => (),
+        Err(e) => warn!(
+            LOG,
+            "Could not find tournament of heat {}: {:?}", heat_id, e
+        ),
+    }
+}
+
+pub async fn notify_tournament_schedule(db: &Pool, notifier: &Notifier, tournament_id: u32) {
+    match Schedule::by_tournament_id(db, tournament_id).await {
+        Ok(schedule) => notifier
+            .send(
+                Channel::Schedule,
+                json!({
+                    "tournament_id": tournament_id,
+                    "schedule": schedule
+                }),
+            )
+            .unwrap(),
+        Err(e) => warn!(
+            LOG,
+            "Could not compute schedule for tournament {}: {:?}", tournament_id, e
+        ),
+    }
+}
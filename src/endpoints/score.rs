@@ -1,10 +1,28 @@
 use crate::authorization::AuthorizedUser;
 use crate::database::Pool;
+use crate::logging::LOG;
+use crate::models::judge::JudgingAssignment;
+use crate::models::missing_score::MissingScores;
+use crate::models::needs::Needs;
 use crate::models::score::{DeleteScore, Score};
+use crate::models::score_lock::ScoreLock;
+use crate::models::score_override::{NewScoreOverride, ScoreOverride};
+use crate::models::score_submission::{
+    ScoreBatchItem, ScoreBatchOutcome, ScoreBatchStatus, ScoreOperation, ScoreSubmission,
+};
+use crate::models::wave_log::LoggedWave;
 use crate::notifier::{Channel, Notifier};
+use crate::score_computation::{missing_scores::MissingScore, Points};
 
-use actix_web::{error, web, Result};
+use actix_web::{error, web, HttpResponse, Result};
+use serde::Deserialize;
 use serde_json::json;
+use slog::warn;
+
+#[derive(Debug, Deserialize)]
+pub struct ScoreVersion {
+    pub version: Option<i32>,
+}
 
 pub async fn get_by_heat_id_and_judge_id(
     path: web::Path<(u32, u32)>,
@@ -42,44 +60,388 @@ pub async fn put(
     notifier: web::Data<Notifier>,
     user: AuthorizedUser,
 ) -> Result<web::Json<Option<Score>>> {
+    let can_manage_heat = can_manage_heat(db.get_ref(), &user, score.heat_id as u32).await?;
+
     // compare given judge_id with session
-    if (user.0.id != score.judge_id as u32) && (!user.0.is_admin()) {
+    if (user.0.id != score.judge_id as u32) && (!can_manage_heat) {
         return Err(error::ErrorForbidden(format!(
             "Judge '{}' not allowed to add score for judge '{}' ",
             user.0.id, score.judge_id
         )));
     }
+    if !can_manage_heat {
+        check_unlocked(db.get_ref(), score.heat_id as u32).await?;
+    }
+    if !score.missed {
+        check_score_range(score.score)?;
+    }
 
-    let result = Score::add(db.get_ref(), &score).await.map_err(|e| {
-        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
-    })?;
-    notifier
-        .send(
-            Channel::Scores,
-            json!({
-                "heat_id": score.heat_id,
-                "judge_id": user.0.id
-            }),
+    check_logged_wave(
+        db.get_ref(),
+        score.heat_id as u32,
+        score.surfer_id as u32,
+        score.wave,
+    )
+    .await?;
+
+    let result = Score::add(db.get_ref(), &score, can_manage_heat)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if result.is_none() {
+        check_current_version(
+            db.get_ref(),
+            score.heat_id,
+            score.judge_id,
+            score.surfer_id,
+            score.wave,
+            score.version,
         )
-        .unwrap();
+        .await?;
+    }
+    notify_scores(db.get_ref(), &notifier, score.heat_id as u32, user.0.id).await;
+
     Ok(web::Json(result))
 }
 
 pub async fn delete(
     delete_score: web::Path<DeleteScore>,
+    version: web::Query<ScoreVersion>,
     db: web::Data<Pool>,
     notifier: web::Data<Notifier>,
     user: AuthorizedUser,
 ) -> Result<web::Json<Option<Score>>> {
+    let mut delete_score = delete_score.into_inner();
+    delete_score.version = version.version;
+    if delete_score.version.is_none() {
+        return Err(error::ErrorPreconditionRequired(
+            "Deleting a score requires its version",
+        ));
+    }
+    let can_manage_heat = can_manage_heat(db.get_ref(), &user, delete_score.heat_id as u32).await?;
+
     // compare given judge_id with session
-    if (user.0.id != delete_score.judge_id as u32) && (!user.0.is_admin()) {
+    if (user.0.id != delete_score.judge_id as u32) && (!can_manage_heat) {
         return Err(error::ErrorForbidden(format!(
             "Judge '{}' not allowed to delete score for judge '{}' ",
             user.0.id, delete_score.judge_id
         )));
     }
+    if !can_manage_heat {
+        check_unlocked(db.get_ref(), delete_score.heat_id as u32).await?;
+    }
+
+    let result = Score::delete(db.get_ref(), &delete_score, can_manage_heat)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if result.is_none() {
+        check_current_version(
+            db.get_ref(),
+            delete_score.heat_id,
+            delete_score.judge_id,
+            delete_score.surfer_id,
+            delete_score.wave,
+            delete_score.version,
+        )
+        .await?;
+    }
+    notify_scores(
+        db.get_ref(),
+        &notifier,
+        delete_score.heat_id as u32,
+        user.0.id,
+    )
+    .await;
+    Ok(web::Json(result))
+}
+
+// apply score changes of a judging tablet in the order they were recorded (in one transaction)
+pub async fn post_batch(
+    path: web::Path<u32>,
+    web::Json(mut items): web::Json<Vec<ScoreBatchItem>>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Vec<ScoreBatchOutcome>>> {
+    let heat_id = path.into_inner();
+    let db_error = |e: anyhow::Error| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    };
+    let can_manage_heat = can_manage_heat(db.get_ref(), &user, heat_id).await?;
+    let locked = if can_manage_heat {
+        false
+    } else {
+        ScoreLock::is_locked(db.get_ref(), heat_id)
+            .await
+            .map_err(db_error)?
+    };
+
+    // stable sort keeps the submitted order for equal timestamps
+    items.sort_by_key(|item| item.client_datetime);
+    let mut tx = db.begin().await.map_err(|e| db_error(e.into()))?;
+    let mut outcomes = Vec::new();
+    for item in items.iter() {
+        let (item_heat_id, judge_id, surfer_id, wave, version) = match &item.operation {
+            ScoreOperation::Upsert(s) => (s.heat_id, s.judge_id, s.surfer_id, s.wave, s.version),
+            ScoreOperation::Delete(s) => (s.heat_id, s.judge_id, s.surfer_id, s.wave, s.version),
+        };
+        let key = &item.idempotency_key;
+
+        if item_heat_id as u32 != heat_id {
+            outcomes.push(ScoreBatchOutcome::rejected(
+                key,
+                format!("Score is not for heat '{}'", heat_id),
+            ));
+            continue;
+        }
+        if (user.0.id != judge_id as u32) && (!can_manage_heat) {
+            outcomes.push(ScoreBatchOutcome::rejected(
+                key,
+                format!(
+                    "Judge '{}' not allowed to change score for judge '{}'",
+                    user.0.id, judge_id
+                ),
+            ));
+            continue;
+        }
+        if locked {
+            outcomes.push(ScoreBatchOutcome::rejected(
+                key,
+                format!("Scores of heat '{}' are locked", heat_id),
+            ));
+            continue;
+        }
+        match &item.operation {
+            ScoreOperation::Upsert(score) if !score.missed => {
+                if let Err(e) = check_score_range(score.score) {
+                    outcomes.push(ScoreBatchOutcome::rejected(key, e.to_string()));
+                    continue;
+                }
+            }
+            ScoreOperation::Delete(_) if version.is_none() => {
+                outcomes.push(ScoreBatchOutcome::rejected(
+                    key,
+                    "Deleting a score requires its version".to_string(),
+                ));
+                continue;
+            }
+            _ => (),
+        }
+        // the recorded key is the gate against applying an item twice
+        let claimed = ScoreSubmission::claim(&mut tx, judge_id as u32, heat_id, item)
+            .await
+            .map_err(db_error)?;
+        if claimed.is_none() {
+            outcomes.push(ScoreBatchOutcome {
+                idempotency_key: key.clone(),
+                status: ScoreBatchStatus::Duplicate,
+                score: None,
+                error: None,
+            });
+            continue;
+        }
+
+        let result = match &item.operation {
+            ScoreOperation::Upsert(score) => {
+                let logged = LoggedWave::is_valid_wave(&mut tx, heat_id, surfer_id as u32, wave)
+                    .await
+                    .map_err(db_error)?;
+                if !logged {
+                    ScoreSubmission::release(&mut tx, judge_id as u32, key)
+                        .await
+                        .map_err(db_error)?;
+                    outcomes.push(ScoreBatchOutcome::rejected(
+                        key,
+                        format!(
+                            "Wave '{}' of surfer '{}' has not been logged for heat '{}'",
+                            wave, surfer_id, heat_id
+                        ),
+                    ));
+                    continue;
+                }
+                Score::add(&mut tx, score, can_manage_heat)
+                    .await
+                    .map_err(db_error)?
+            }
+            ScoreOperation::Delete(delete_score) => {
+                Score::delete(&mut tx, delete_score, can_manage_heat)
+                    .await
+                    .map_err(db_error)?
+            }
+        };
+
+        match result {
+            Some(score) => {
+                outcomes.push(ScoreBatchOutcome {
+                    idempotency_key: key.clone(),
+                    status: ScoreBatchStatus::Applied,
+                    score: Some(score),
+                    error: None,
+                });
+            }
+            None => {
+                ScoreSubmission::release(&mut tx, judge_id as u32, key)
+                    .await
+                    .map_err(db_error)?;
+                let current = Score::find_one(&mut tx, heat_id as i32, judge_id, surfer_id, wave)
+                    .await
+                    .map_err(db_error)?;
+                match (current, version) {
+                    (Some(current), None) => outcomes.push(ScoreBatchOutcome {
+                        idempotency_key: key.clone(),
+                        status: ScoreBatchStatus::Conflict,
+                        error: Some("Changing a score requires its version".to_string()),
+                        score: Some(current),
+                    }),
+                    (Some(current), Some(version)) if current.version != Some(version) => {
+                        outcomes.push(ScoreBatchOutcome {
+                            idempotency_key: key.clone(),
+                            status: ScoreBatchStatus::Conflict,
+                            error: Some(format!("Score is based on outdated version '{}'", version)),
+                            score: Some(current),
+                        })
+                    }
+                    _ => outcomes.push(ScoreBatchOutcome::rejected(
+                        key,
+                        format!(
+                            "Score of judge '{}' for surfer '{}' and wave '{}' could not be changed",
+                            judge_id, surfer_id, wave
+                        ),
+                    )),
+                }
+            }
+        }
+    }
+    tx.commit().await.map_err(|e| db_error(e.into()))?;
+
+    if outcomes
+        .iter()
+        .any(|o| o.status == ScoreBatchStatus::Applied)
+    {
+        notify_scores(db.get_ref(), &notifier, heat_id, user.0.id).await;
+    }
+    Ok(web::Json(outcomes))
+}
+
+pub async fn get_missing_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    _user: AuthorizedUser,
+) -> Result<web::Json<Vec<MissingScore>>> {
+    let heat_id = path.into_inner();
+    let result = MissingScores::by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    Ok(web::Json(result))
+}
+
+pub async fn get_overrides_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Vec<ScoreOverride>>> {
+    let heat_id = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+
+    let result = ScoreOverride::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    Ok(web::Json(result))
+}
+
+pub async fn put_override(
+    path: web::Path<u32>,
+    web::Json(score_override): web::Json<NewScoreOverride>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<ScoreOverride>>> {
+    let heat_id = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+
+    if !score_override.voided && score_override.score.is_none() {
+        return Err(error::ErrorBadRequest(
+            "Score override needs either a score or has to void the wave",
+        ));
+    }
+    if score_override.reason.trim().is_empty() {
+        return Err(error::ErrorBadRequest("Score override needs a reason"));
+    }
+    if let Some(score) = score_override.score {
+        check_score_range(score)?;
+    }
+
+    let result = ScoreOverride::add(db.get_ref(), heat_id, user.0.id, &score_override)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notify_scores(db.get_ref(), &notifier, heat_id, user.0.id).await;
+    Ok(web::Json(result))
+}
+
+pub async fn delete_override(
+    path: web::Path<(u32, u32, i32)>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<ScoreOverride>>> {
+    let (heat_id, surfer_id, wave) = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+
+    let result = ScoreOverride::delete(db.get_ref(), heat_id, surfer_id, wave)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notify_scores(db.get_ref(), &notifier, heat_id, user.0.id).await;
+    Ok(web::Json(result))
+}
+
+pub async fn get_lock_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<ScoreLock>>> {
+    let heat_id = path.into_inner();
+    // judges of the heat need to know when their scores get locked
+    let assigned = JudgingAssignment::is_assigned(db.get_ref(), heat_id, user.0.id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if !assigned {
+        check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+    }
+
+    let result = ScoreLock::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    Ok(web::Json(result))
+}
+
+pub async fn lock(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<&'static str>> {
+    let heat_id = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
 
-    let result = Score::delete(db.get_ref(), &delete_score)
+    ScoreLock::lock(db.get_ref(), heat_id, user.0.id)
         .await
         .map_err(|e| {
             error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
@@ -88,10 +450,166 @@ pub async fn delete(
         .send(
             Channel::Scores,
             json!({
-                "heat_id": delete_score.heat_id,
-                "judge_id": user.0.id
+                "heat_id": heat_id,
+                "msg": "lock_scores"
             }),
         )
         .unwrap();
-    Ok(web::Json(result))
+    Ok(web::Json("Locked scores!"))
+}
+
+pub async fn unlock(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<&'static str>> {
+    let heat_id = path.into_inner();
+    check_can_manage_heat(db.get_ref(), &user, heat_id).await?;
+
+    ScoreLock::unlock(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    notifier
+        .send(
+            Channel::Scores,
+            json!({
+                "heat_id": heat_id,
+                "msg": "unlock_scores"
+            }),
+        )
+        .unwrap();
+    Ok(web::Json("Unlocked scores!"))
+}
+
+// notify about changed scores and the resulting missing scores and needs of the heat
+async fn notify_scores(db: &Pool, notifier: &Notifier, heat_id: u32, judge_id: u32) {
+    notifier
+        .send(
+            Channel::Scores,
+            json!({
+                "heat_id": heat_id,
+                "judge_id": judge_id
+            }),
+        )
+        .unwrap();
+
+    match MissingScores::by_heat_id(db, heat_id).await {
+        Ok(missing_scores) => notifier
+            .send(
+                Channel::MissingScores,
+                json!({
+                    "heat_id": heat_id,
+                    "missing_scores": missing_scores
+                }),
+            )
+            .unwrap(),
+        Err(e) => warn!(
+            LOG,
+            "Could not compute missing scores for heat {}: {:?}", heat_id, e
+        ),
+    }
+
+    match Needs::by_heat_id(db, heat_id).await {
+        Ok(needs) => notifier
+            .send(
+                Channel::Results,
+                json!({
+                    "heat_id": heat_id,
+                    "needs": needs
+                }),
+            )
+            .unwrap(),
+        Err(e) => warn!(LOG, "Could not compute needs for heat {}: {:?}", heat_id, e),
+    }
+}
+
+async fn can_manage_heat(db: &Pool, user: &AuthorizedUser, heat_id: u32) -> Result<bool> {
+    user.can_manage_heat(db, heat_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })
+}
+
+async fn check_can_manage_heat(db: &Pool, user: &AuthorizedUser, heat_id: u32) -> Result<()> {
+    if !can_manage_heat(db, user, heat_id).await? {
+        return Err(error::ErrorForbidden(format!(
+            "User '{}' not allowed to manage scores of heat '{}'",
+            user.0.username, heat_id
+        )));
+    }
+    Ok(())
+}
+
+async fn check_unlocked(db: &Pool, heat_id: u32) -> Result<()> {
+    let locked = ScoreLock::is_locked(db, heat_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    if locked {
+        return Err(error::ErrorForbidden(format!(
+            "Scores of heat '{}' are locked",
+            heat_id
+        )));
+    }
+    Ok(())
+}
+
+fn check_score_range(score: Points) -> Result<()> {
+    if score < Points::ZERO || score > Points::MAX_WAVE_SCORE {
+        return Err(error::ErrorBadRequest(format!(
+            "Score '{}' is not between {} and {}",
+            score,
+            Points::ZERO,
+            Points::MAX_WAVE_SCORE
+        )));
+    }
+    Ok(())
+}
+
+async fn check_logged_wave(db: &Pool, heat_id: u32, surfer_id: u32, wave: i32) -> Result<()> {
+    let valid = LoggedWave::is_valid_wave(db, heat_id, surfer_id, wave)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if !valid {
+        return Err(error::ErrorBadRequest(format!(
+            "Wave '{}' of surfer '{}' has not been logged for heat '{}'",
+            wave, surfer_id, heat_id
+        )));
+    }
+    Ok(())
+}
+
+// a change of an existing score needs its current version (the response carries the current score)
+async fn check_current_version(
+    db: &Pool,
+    heat_id: i32,
+    judge_id: i32,
+    surfer_id: i32,
+    wave: i32,
+    version: Option<i32>,
+) -> Result<()> {
+    let current = Score::find_one(db, heat_id, judge_id, surfer_id, wave)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    match (current, version) {
+        // only new scores may be added without a version
+        (Some(current), None) => Err(error::InternalError::from_response(
+            "Changing a score requires its version",
+            HttpResponse::PreconditionRequired().json(&current),
+        )
+        .into()),
+        (Some(current), Some(version)) if current.version != Some(version) => {
+            Err(error::InternalError::from_response(
+                format!("Score is based on outdated version '{}'", version),
+                HttpResponse::Conflict().json(&current),
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
 }
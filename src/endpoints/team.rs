@@ -0,0 +1,127 @@
+use crate::authorization::AuthorizedUser;
+use crate::database::Pool;
+use crate::logging::LOG;
+use crate::models::team::{NewTeam, Team, TeamMember};
+use crate::models::team_standing::TeamStandings;
+use crate::score_computation::team_standings::TeamStanding;
+
+use actix_web::{error, web, Result};
+use slog::info;
+
+pub async fn get_by_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Option<Team>>> {
+    let team_id = path.into_inner();
+    let result = Team::find_by_id(db.get_ref(), team_id, true)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_by_tournament_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<Team>>> {
+    let tournament_id = path.into_inner();
+    let result = Team::find_by_tournament_id(db.get_ref(), tournament_id, true)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_standings_by_category_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<TeamStanding>>> {
+    let category_id = path.into_inner();
+    let result = TeamStandings::by_category_id(db.get_ref(), category_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_standings_by_tournament_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Vec<TeamStanding>>> {
+    let tournament_id = path.into_inner();
+    let result = TeamStandings::by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn post(
+    path: web::Path<u32>,
+    web::Json(team): web::Json<NewTeam>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Team>> {
+    let tournament_id = path.into_inner();
+    let result = Team::add(db.get_ref(), tournament_id, &team)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(
+        LOG,
+        "Added team {} to tournament {} by {}", result.id, tournament_id, user
+    );
+    Ok(web::Json(result))
+}
+
+pub async fn delete(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<Team>>> {
+    let team_id = path.into_inner();
+    let result = Team::delete(db.get_ref(), team_id).await.map_err(|e| {
+        error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+    })?;
+    info!(LOG, "Deleted team {} by {}", team_id, user);
+    Ok(web::Json(result))
+}
+
+pub async fn add_member(
+    path: web::Path<(u32, u32)>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<TeamMember>> {
+    let (team_id, surfer_id) = path.into_inner();
+    let result = TeamMember::add(db.get_ref(), team_id, surfer_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    match result {
+        Some(member) => Ok(web::Json(member)),
+        None => Err(error::ErrorBadRequest(format!(
+            "Surfer '{}' can not be added to team '{}' (already member of a team of the tournament?)",
+            surfer_id, team_id
+        ))),
+    }
+}
+
+pub async fn delete_member(
+    path: web::Path<(u32, u32)>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Option<TeamMember>>> {
+    let (team_id, surfer_id) = path.into_inner();
+    let result = TeamMember::delete(db.get_ref(), team_id, surfer_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
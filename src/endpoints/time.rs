@@ -0,0 +1,34 @@
+use actix_web::{web, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+// reply to a clock synchronisation request (times in milliseconds since epoch)
+// clients estimate their clock offset from the round trip like NTP:
+// offset = ((server_receive_time - client_time) + (server_send_time - now)) / 2
+#[derive(Debug, Serialize)]
+pub struct TimeSync {
+    // the time sent by the client, returned as is
+    pub client_time: Option<f64>,
+    pub server_receive_time: i64,
+    pub server_send_time: i64,
+}
+
+impl TimeSync {
+    pub fn new(client_time: Option<f64>, server_receive_time: i64) -> Self {
+        TimeSync {
+            client_time,
+            server_receive_time,
+            server_send_time: Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeSyncRequest {
+    pub client_time: Option<f64>,
+}
+
+pub async fn get_time(request: web::Query<TimeSyncRequest>) -> Result<web::Json<TimeSync>> {
+    let received = Utc::now().timestamp_millis();
+    Ok(web::Json(TimeSync::new(request.client_time, received)))
+}
@@ -1,7 +1,31 @@
+use crate::authorization::AuthorizedUser;
 use crate::database::Pool;
-use crate::models::tournament::Tournament;
+use crate::endpoints::schedule::notify_tournament_schedule;
+use crate::logging::LOG;
+use crate::models::{
+    armed_heat::ArmedHeat,
+    heat::Heat,
+    heat_state::HeatState,
+    tournament::Tournament,
+    tournament_hold::{NewTournamentHold, TournamentHold},
+};
+use crate::notifier::{Channel, Notifier};
 
 use actix_web::{error, web, Result};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use slog::info;
+
+#[derive(Debug, Deserialize)]
+pub struct TimeZoneSetting {
+    pub time_zone: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AutoStartSetting {
+    pub auto_start: bool,
+}
 
 pub async fn get_all(db: web::Data<Pool>) -> Result<web::Json<Vec<Tournament>>> {
     let result = Tournament::find_all(db.get_ref()).await.map_err(|e| {
@@ -22,3 +46,168 @@ pub async fn get_by_id(
         })?;
     Ok(web::Json(result))
 }
+
+pub async fn put_time_zone(
+    path: web::Path<u32>,
+    web::Json(setting): web::Json<TimeZoneSetting>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<Tournament>>> {
+    let tournament_id = path.into_inner();
+    let time_zone: Tz = setting.time_zone.parse().map_err(|_| {
+        error::ErrorBadRequest(format!("Unknown time zone '{}'", setting.time_zone))
+    })?;
+    let result = Tournament::set_time_zone(db.get_ref(), tournament_id, &time_zone)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(
+        LOG,
+        "Set time zone of tournament {} to {} by {}", tournament_id, time_zone, user
+    );
+    Ok(web::Json(result))
+}
+
+pub async fn put_auto_start(
+    path: web::Path<u32>,
+    web::Json(setting): web::Json<AutoStartSetting>,
+    db: web::Data<Pool>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<Tournament>>> {
+    let tournament_id = path.into_inner();
+    let result = Tournament::set_auto_start(db.get_ref(), tournament_id, setting.auto_start)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(
+        LOG,
+        "Set auto start of tournament {} to {} by {}", tournament_id, setting.auto_start, user
+    );
+    Ok(web::Json(result))
+}
+
+pub async fn get_armed_heats(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    _: AuthorizedUser,
+) -> Result<web::Json<Vec<ArmedHeat>>> {
+    let tournament_id = path.into_inner();
+    let result = ArmedHeat::find_by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn get_hold(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+) -> Result<web::Json<Option<TournamentHold>>> {
+    let tournament_id = path.into_inner();
+    let result = TournamentHold::find_by_tournament_id(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+// put the tournament on hold and pause all of its running heats
+pub async fn put_hold(
+    path: web::Path<u32>,
+    web::Json(hold): web::Json<NewTournamentHold>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<TournamentHold>> {
+    let tournament_id = path.into_inner();
+    // the reason is shown to everyone waiting for the tournament to continue
+    if hold.reason.trim().is_empty() {
+        return Err(error::ErrorBadRequest("A hold needs a reason"));
+    }
+    let result = TournamentHold::hold(db.get_ref(), tournament_id, &hold, user.0.id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    info!(
+        LOG,
+        "Put tournament {} on hold ({}) by {}", tournament_id, hold.reason, user
+    );
+
+    let active_heats = Heat::find_active_heats_by_tournament_id(db.get_ref(), tournament_id, false)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    for heat in active_heats.iter() {
+        let paused = HeatState::set_heat_paused(db.get_ref(), heat.id as u32, Some(user.0.id))
+            .await
+            .map_err(|e| {
+                error::ErrorInternalServerError(format!(
+                    "Error fetching data from database: {:?}",
+                    e
+                ))
+            })?;
+        if paused {
+            notifier
+                .send(
+                    Channel::ActiveHeats,
+                    json!({
+                        "heat_id": heat.id,
+                        "msg": "toggle_heat_pause"
+                    }),
+                )
+                .unwrap();
+        }
+    }
+
+    notify_all_channels(
+        &notifier,
+        json!({
+            "tournament_id": tournament_id,
+            "msg": "tournament_hold",
+            "hold": result
+        }),
+    );
+    notify_tournament_schedule(db.get_ref(), &notifier, tournament_id).await;
+    Ok(web::Json(result))
+}
+
+// paused heats stay paused until they are continued one by one
+pub async fn delete_hold(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<bool>> {
+    let tournament_id = path.into_inner();
+    let result = TournamentHold::release(db.get_ref(), tournament_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    if result {
+        info!(
+            LOG,
+            "Release hold of tournament {} by {}", tournament_id, user
+        );
+        notify_all_channels(
+            &notifier,
+            json!({
+                "tournament_id": tournament_id,
+                "msg": "tournament_hold_released"
+            }),
+        );
+    }
+    Ok(web::Json(result))
+}
+
+fn notify_all_channels(notifier: &Notifier, message: Value) {
+    for channel in Channel::all() {
+        notifier.send(channel, message.clone()).unwrap();
+    }
+}
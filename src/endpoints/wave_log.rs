@@ -0,0 +1,97 @@
+use crate::authorization::AuthorizedUser;
+use crate::database::Pool;
+use crate::endpoints::priority;
+use crate::logging::LOG;
+use crate::models::wave_log::{LoggedWave, NewLoggedWave};
+use crate::notifier::{Channel, Notifier};
+
+use actix_web::{error, web, Result};
+use serde_json::json;
+use slog::info;
+
+pub async fn get_by_heat_id(
+    path: web::Path<u32>,
+    db: web::Data<Pool>,
+    _user: AuthorizedUser,
+) -> Result<web::Json<Vec<LoggedWave>>> {
+    let heat_id = path.into_inner();
+    let result = LoggedWave::find_by_heat_id(db.get_ref(), heat_id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+    Ok(web::Json(result))
+}
+
+pub async fn post(
+    path: web::Path<u32>,
+    web::Json(wave): web::Json<NewLoggedWave>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<LoggedWave>>> {
+    let heat_id = path.into_inner();
+    let result = LoggedWave::add(db.get_ref(), heat_id, wave.surfer_id as u32, user.0.id)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    if let Some(logged_wave) = &result {
+        info!(
+            LOG,
+            "Logged wave {} of surfer {} in heat {} by {}",
+            logged_wave.wave,
+            logged_wave.surfer_id,
+            heat_id,
+            user
+        );
+        notifier
+            .send(
+                Channel::Waves,
+                json!({
+                    "heat_id": heat_id,
+                    "surfer_id": logged_wave.surfer_id,
+                    "wave": logged_wave.wave,
+                    "msg": "wave_logged"
+                }),
+            )
+            .unwrap();
+        priority::surfer_rode_wave(db.get_ref(), &notifier, heat_id, wave.surfer_id as u32).await;
+    }
+    Ok(web::Json(result))
+}
+
+pub async fn delete(
+    path: web::Path<(u32, u32, i32)>,
+    db: web::Data<Pool>,
+    notifier: web::Data<Notifier>,
+    user: AuthorizedUser,
+) -> Result<web::Json<Option<LoggedWave>>> {
+    let (heat_id, surfer_id, wave) = path.into_inner();
+    let result = LoggedWave::delete(db.get_ref(), heat_id, surfer_id, wave)
+        .await
+        .map_err(|e| {
+            error::ErrorInternalServerError(format!("Error fetching data from database: {:?}", e))
+        })?;
+
+    info!(
+        LOG,
+        "Deleted wave {} of surfer {} in heat {} by {}", wave, surfer_id, heat_id, user
+    );
+    notifier
+        .send(
+            Channel::Waves,
+            json!({
+                "heat_id": heat_id,
+                "surfer_id": surfer_id,
+                "wave": wave,
+                "msg": "wave_deleted"
+            }),
+        )
+        .unwrap();
+    if result.is_some() {
+        priority::wave_deleted(db.get_ref(), &notifier, heat_id).await;
+    }
+    Ok(web::Json(result))
+}
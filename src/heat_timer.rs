@@ -0,0 +1,168 @@
+use crate::configuration::CONFIG;
+use crate::database::Pool;
+use crate::endpoints::heat_state::{notify_heat_started, notify_heat_stopped};
+use crate::logging::LOG;
+use crate::models::{
+    heat_state::HeatState, schedule::Schedule, tournament::Tournament,
+    tournament_hold::TournamentHold,
+};
+use crate::notifier::{Channel, Notifier};
+
+use actix::prelude::*;
+use chrono::Utc;
+use serde_json::json;
+use slog::{info, warn};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// actor watching the clock of running heats
+// sends the configured signals on the active heats channel and finishes heats whose time is up
+// also starts armed heats of tournaments with auto start when they are due
+pub struct HeatTimer {
+    db: Pool,
+    notifier: Notifier,
+    // remaining time of each running heat at the last tick
+    remaining_s: HashMap<i32, f64>,
+    busy: bool,
+}
+
+impl HeatTimer {
+    pub fn new(db: Pool, notifier: Notifier) -> Self {
+        Self {
+            db,
+            notifier,
+            remaining_s: HashMap::new(),
+            busy: false,
+        }
+    }
+
+    fn tick(&mut self, ctx: &mut Context<Self>) {
+        // skip ticks while the database is still busy with the previous one
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+
+        let db = self.db.clone();
+        let notifier = self.notifier.clone();
+        let fut = async move {
+            start_due_heats(&db, &notifier).await;
+            HeatState::find_running(&db).await
+        };
+        ctx.spawn(fut.into_actor(self).map(|res, act, ctx| {
+            act.busy = false;
+            match res {
+                Ok(heat_states) => act.update(heat_states, ctx),
+                Err(e) => warn!(LOG, "Could not fetch running heats: {:?}", e),
+            }
+        }));
+    }
+
+    fn update(&mut self, heat_states: Vec<HeatState>, ctx: &mut Context<Self>) {
+        let now = Utc::now();
+        let mut remaining_s = HashMap::new();
+        for heat_state in heat_states.iter() {
+            let heat_id = heat_state.heat_id;
+            let remaining = (heat_state.end_datetime - now).num_milliseconds() as f64 / 1000.0;
+
+            // a signal is due when the remaining time passed it since the last tick
+            // (heats seen for the first time only start counting, time resets start over)
+            if let Some(previous) = self.remaining_s.get(&heat_id) {
+                for signal_s in CONFIG.heat_timer.signals_s.iter() {
+                    if *previous > *signal_s && remaining <= *signal_s {
+                        self.notifier
+                            .send(
+                                Channel::ActiveHeats,
+                                json!({
+                                    "heat_id": heat_id,
+                                    "msg": "heat_signal",
+                                    "remaining_time_s": signal_s,
+                                }),
+                            )
+                            .unwrap();
+                    }
+                }
+            }
+
+            if CONFIG.heat_timer.auto_stop && remaining <= 0.0 {
+                self.stop_heat(heat_id as u32, ctx);
+            } else {
+                remaining_s.insert(heat_id, remaining);
+            }
+        }
+        self.remaining_s = remaining_s;
+    }
+
+    fn stop_heat(&self, heat_id: u32, ctx: &mut Context<Self>) {
+        let db = self.db.clone();
+        let notifier = self.notifier.clone();
+        let fut = async move {
+            match HeatState::set_heat_stopped(&db, heat_id, None, CONFIG.judging.score_lock_grace_s)
+                .await
+            {
+                Ok(true) => {
+                    info!(LOG, "Heat {} finished (time is up)", heat_id);
+                    notify_heat_stopped(&db, &notifier, heat_id).await;
+                }
+                // stopped by someone else in the meantime
+                Ok(false) => (),
+                Err(e) => warn!(LOG, "Could not stop heat {}: {:?}", heat_id, e),
+            }
+        };
+        ctx.spawn(fut.into_actor(self));
+    }
+}
+
+async fn start_due_heats(db: &Pool, notifier: &Notifier) {
+    let tournaments = match Tournament::find_auto_start(db).await {
+        Ok(tournaments) => tournaments,
+        Err(e) => {
+            warn!(LOG, "Could not fetch tournaments with auto start: {:?}", e);
+            return;
+        }
+    };
+    for tournament in tournaments.iter() {
+        match TournamentHold::find_by_tournament_id(db, tournament.id as u32).await {
+            Ok(None) => (),
+            Ok(Some(_)) => continue,
+            Err(e) => {
+                warn!(
+                    LOG,
+                    "Could not fetch hold of tournament {}: {:?}", tournament.id, e
+                );
+                continue;
+            }
+        }
+        let due_heats = match Schedule::due_heats_by_tournament_id(db, tournament.id as u32).await {
+            Ok(due_heats) => due_heats,
+            Err(e) => {
+                warn!(
+                    LOG,
+                    "Could not compute due heats of tournament {}: {:?}", tournament.id, e
+                );
+                continue;
+            }
+        };
+        for heat_id in due_heats {
+            match HeatState::set_heat_started(db, heat_id as u32, None).await {
+                Ok(true) => {
+                    info!(LOG, "Start heat {} automatically", heat_id);
+                    notify_heat_started(db, notifier, heat_id as u32).await;
+                }
+                Ok(false) => (),
+                Err(e) => warn!(LOG, "Could not start heat {}: {:?}", heat_id, e),
+            }
+        }
+    }
+}
+
+impl Actor for HeatTimer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(
+            Duration::from_millis(CONFIG.heat_timer.interval_ms),
+            |act, ctx| act.tick(ctx),
+        );
+    }
+}
@@ -18,6 +18,7 @@ mod authorization;
 mod configuration;
 mod database;
 mod endpoints;
+mod heat_timer;
 mod logging;
 mod models;
 mod notifier;
@@ -88,6 +89,11 @@ async fn main() -> Result<()> {
         zmq_receiver.start().await?;
     };
 
+    if CONFIG.heat_timer.enabled {
+        info!(LOG, "Starting heat timer");
+        heat_timer::HeatTimer::new(pool.get_ref().clone(), notifier.clone()).start();
+    }
+
     let notifier = Data::new(notifier);
 
     let private_key = rand::thread_rng().gen::<[u8; 32]>();
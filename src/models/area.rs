@@ -0,0 +1,124 @@
+use crate::database::Pool;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Postgres, Transaction};
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Area {
+    pub id: i32,
+    pub tournament_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewArea {
+    pub name: String,
+}
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AreaJudge {
+    pub area_id: i32,
+    pub judge_id: i32,
+}
+
+impl Area {
+    pub async fn find_by_id(db: &Pool, area_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, Area>(r#"SELECT * FROM areas WHERE id = $1"#)
+            .bind(area_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn find_by_tournament_id(db: &Pool, tournament_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, Area>(
+            r#"SELECT * FROM areas WHERE tournament_id = $1 ORDER BY name"#,
+        )
+        .bind(tournament_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn add(db: &Pool, tournament_id: u32, area: &NewArea) -> anyhow::Result<Self> {
+        let res = sqlx::query_as::<_, Area>(
+            r#"INSERT INTO areas (tournament_id, name) VALUES ($1, $2) RETURNING *"#,
+        )
+        .bind(tournament_id)
+        .bind(&area.name)
+        .fetch_one(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn delete(db: &Pool, area_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, Area>(r#"DELETE FROM areas WHERE id = $1 RETURNING *"#)
+            .bind(area_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+}
+
+impl AreaJudge {
+    pub async fn find_by_area_id(db: &Pool, area_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, AreaJudge>(r#"SELECT * FROM area_judges WHERE area_id = $1"#)
+            .bind(area_id)
+            .fetch_all(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn add(db: &Pool, area_id: u32, judge_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+INSERT INTO area_judges (area_id, judge_id)
+VALUES ($1, $2)
+ON CONFLICT (area_id, judge_id) DO NOTHING;
+        "#,
+        )
+        .bind(area_id)
+        .bind(judge_id)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete(db: &Pool, area_id: u32, judge_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(r#"DELETE FROM area_judges WHERE area_id = $1 AND judge_id = $2"#)
+            .bind(area_id)
+            .bind(judge_id)
+            .execute(db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // assign the current panel of the heat's area to the heat (replacing the panel it had before)
+    // judges assigned to the heat itself stay assigned
+    pub async fn assign_to_heat(
+        tx: &mut Transaction<'_, Postgres>,
+        heat_id: u32,
+    ) -> anyhow::Result<bool> {
+        sqlx::query(r#"DELETE FROM judge_assignments WHERE heat_id = $1 AND from_area"#)
+            .bind(heat_id)
+            .execute(&mut *tx)
+            .await?;
+        let res = sqlx::query(
+            r#"
+INSERT INTO judge_assignments (heat_id, judge_id, from_area)
+SELECT h.id, aj.judge_id, TRUE
+FROM heats h
+INNER JOIN area_judges aj
+ON aj.area_id = h.area_id
+WHERE h.id = $1
+ON CONFLICT (heat_id, judge_id) DO NOTHING;
+        "#,
+        )
+        .bind(heat_id)
+        .execute(&mut *tx)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
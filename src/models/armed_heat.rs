@@ -0,0 +1,57 @@
+use crate::database::Pool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// heat that may be started automatically (in tournaments with auto start)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ArmedHeat {
+    pub heat_id: i32,
+    pub armed_datetime: DateTime<Utc>,
+    pub armed_by: Option<i32>,
+}
+
+impl ArmedHeat {
+    pub async fn find_by_tournament_id(db: &Pool, tournament_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, ArmedHeat>(
+            r#"
+SELECT a.*
+FROM armed_heats a
+INNER JOIN heats h
+ON a.heat_id = h.id
+  INNER JOIN categories c
+  ON h.category_id = c.id
+WHERE c.tournament_id = $1
+        "#,
+        )
+        .bind(tournament_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn arm(db: &Pool, heat_id: u32, user_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, ArmedHeat>(
+            r#"
+INSERT INTO armed_heats (heat_id, armed_datetime, armed_by)
+VALUES ($1, NOW(), $2)
+ON CONFLICT (heat_id) DO NOTHING
+RETURNING *;
+        "#,
+        )
+        .bind(heat_id)
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn disarm(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(r#"DELETE FROM armed_heats WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
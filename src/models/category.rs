@@ -1,5 +1,5 @@
 use crate::database::Pool;
-use crate::models::tournament::Tournament;
+use crate::models::{result::TieBreak, team::TeamScoring, tournament::Tournament};
 
 use futures::future;
 
@@ -13,6 +13,8 @@ pub struct CategoryCore {
     pub tournament_id: i32,
     pub name: String,
     pub additional_info: Option<String>,
+    pub team_scoring: Option<TeamScoring>,
+    pub tie_break: Option<TieBreak>,
 }
 
 // this struct will be used to represent database record
@@ -22,6 +24,8 @@ pub struct Category {
     pub tournament_id: i32,
     pub name: String,
     pub additional_info: Option<String>,
+    pub team_scoring: Option<TeamScoring>,
+    pub tie_break: Option<TieBreak>,
     pub tournament: Option<Tournament>,
 }
 
@@ -32,6 +36,8 @@ impl From<CategoryCore> for Category {
             tournament_id: category.tournament_id,
             name: category.name,
             additional_info: category.additional_info,
+            team_scoring: category.team_scoring,
+            tie_break: category.tie_break,
             tournament: None,
         }
     }
@@ -136,4 +142,36 @@ impl Category {
             expand
         ).await
     }
+
+    pub async fn set_team_scoring(
+        db: &Pool,
+        category_id: u32,
+        team_scoring: Option<TeamScoring>,
+    ) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, CategoryCore>(
+            r#"UPDATE categories SET team_scoring = $2 WHERE id = $1 RETURNING *"#,
+        )
+        .bind(category_id)
+        .bind(team_scoring)
+        .fetch_optional(db)
+        .await?
+        .map(Self::from);
+        Ok(res)
+    }
+
+    pub async fn set_tie_break(
+        db: &Pool,
+        category_id: u32,
+        tie_break: Option<TieBreak>,
+    ) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, CategoryCore>(
+            r#"UPDATE categories SET tie_break = $2 WHERE id = $1 RETURNING *"#,
+        )
+        .bind(category_id)
+        .bind(tie_break)
+        .fetch_optional(db)
+        .await?
+        .map(Self::from);
+        Ok(res)
+    }
 }
@@ -1,7 +1,7 @@
 use crate::database::Pool;
 use crate::models::participation::Participation; //{category::Category, participation::Participation};
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use futures::future;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
@@ -14,11 +14,12 @@ pub struct HeatCore {
     pub name: String,
     pub round: i32,
     pub number_in_round: i32,
-    pub start_datetime: NaiveDateTime,
+    pub start_datetime: DateTime<Utc>,
     pub number_of_waves: i32,
     pub duration: f64,
     pub heat_type: HeatType,
     pub additional_info: Option<String>,
+    pub area_id: Option<i32>,
 }
 
 // this struct will be used to represent database record
@@ -29,11 +30,12 @@ pub struct Heat {
     pub name: String,
     pub round: i32,
     pub number_in_round: i32,
-    pub start_datetime: NaiveDateTime,
+    pub start_datetime: DateTime<Utc>,
     pub number_of_waves: i32,
     pub duration: f64,
     pub heat_type: HeatType,
     pub additional_info: Option<String>,
+    pub area_id: Option<i32>,
     //pub category: Option<Category>,
     pub participations: Option<Vec<Participation>>,
 }
@@ -51,6 +53,7 @@ impl From<HeatCore> for Heat {
             duration: heat.duration,
             heat_type: heat.heat_type,
             additional_info: heat.additional_info,
+            area_id: heat.area_id,
             //category: None,
             participations: None,
         }
@@ -63,6 +66,7 @@ impl From<HeatCore> for Heat {
 pub enum HeatType {
     Standard,
     Call,
+    Criteria,
 }
 
 impl Heat {
@@ -157,6 +161,48 @@ impl Heat {
         .await
     }
 
+    pub async fn find_by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+        expand: bool,
+    ) -> anyhow::Result<Vec<Self>> {
+        Self::find_vec_bind(
+            db,
+            r#"
+                SELECT h.*
+                FROM heats h
+                INNER JOIN categories c
+                ON h.category_id = c.id
+                WHERE c.tournament_id = $1
+                ORDER BY h.start_datetime, h.id
+            "#,
+            tournament_id,
+            expand,
+        )
+        .await
+    }
+
+    pub async fn find_by_surfer_id(
+        db: &Pool,
+        surfer_id: u32,
+        expand: bool,
+    ) -> anyhow::Result<Vec<Self>> {
+        Self::find_vec_bind(
+            db,
+            r#"
+                SELECT h.*
+                FROM heats h
+                INNER JOIN participations p
+                ON p.heat_id = h.id
+                WHERE p.surfer_id = $1
+                ORDER BY h.start_datetime, h.id
+            "#,
+            surfer_id,
+            expand,
+        )
+        .await
+    }
+
     pub async fn find_active_heats_by_category_id(
         db: &Pool,
         category_id: u32,
@@ -167,7 +213,7 @@ impl Heat {
             r#"
                 SELECT h.*
                 FROM heats h
-                INNER JOIN heat_state s
+                INNER JOIN current_heat_states s
                 ON s.heat_id = h.id
                 WHERE s.state in ('active', 'paused') AND h.category_id = $1
             "#,
@@ -189,7 +235,7 @@ impl Heat {
                 FROM heats h
                 INNER JOIN categories c
                 ON h.category_id = c.id
-                  INNER JOIN heat_state s
+                  INNER JOIN current_heat_states s
                   ON s.heat_id = h.id
                 WHERE s.state in ('active', 'paused') AND c.tournament_id = $1
             "#,
@@ -199,6 +245,52 @@ impl Heat {
         .await
     }
 
+    pub async fn find_active_heats_by_area_id(
+        db: &Pool,
+        area_id: u32,
+        expand: bool,
+    ) -> anyhow::Result<Vec<Self>> {
+        Self::find_vec_bind(
+            db,
+            r#"
+                SELECT h.*
+                FROM heats h
+                INNER JOIN current_heat_states s
+                ON s.heat_id = h.id
+                WHERE s.state in ('active', 'paused') AND h.area_id = $1
+            "#,
+            area_id,
+            expand,
+        )
+        .await
+    }
+
+    // only areas of the heat's tournament, None if the heat or such an area was not found
+    pub async fn set_area(
+        db: &Pool,
+        heat_id: u32,
+        area_id: Option<u32>,
+    ) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, HeatCore>(
+            r#"
+                UPDATE heats h
+                SET area_id = $2
+                FROM categories c
+                WHERE h.id = $1 AND c.id = h.category_id
+                  AND ($2::INTEGER IS NULL OR EXISTS (
+                    SELECT 1 FROM areas a
+                    WHERE a.id = $2 AND a.tournament_id = c.tournament_id))
+                RETURNING h.*
+            "#,
+        )
+        .bind(heat_id)
+        .bind(area_id)
+        .fetch_optional(db)
+        .await?
+        .map(Self::from);
+        Ok(res)
+    }
+
     pub async fn find_active_heats_by_judge_id(
         db: &Pool,
         judge_id: u32,
@@ -209,7 +301,7 @@ impl Heat {
             r#"
                 SELECT h.*
                 FROM heats h
-                INNER JOIN heat_state s
+                INNER JOIN current_heat_states s
                 ON s.heat_id = h.id
                   INNER JOIN judge_assignments ja
                   ON h.id = ja.heat_id
@@ -228,7 +320,7 @@ impl Heat {
             r#"
                 SELECT h.*
                 FROM heats h
-                INNER JOIN heat_state s
+                INNER JOIN current_heat_states s
                 ON s.heat_id = h.id
                 WHERE s.state in ('active', 'paused')
             "#,
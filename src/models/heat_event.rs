@@ -0,0 +1,178 @@
+use crate::database::Pool;
+use crate::models::heat_state::{HeatState, HeatStateType};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, Type};
+
+#[derive(Type, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum HeatEventType {
+    Start,
+    Pause,
+    Unpause,
+    ResetTime,
+    AdjustTime,
+    Stop,
+}
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct HeatEvent {
+    pub id: i32,
+    pub heat_id: i32,
+    pub event: HeatEventType,
+    pub event_datetime: DateTime<Utc>,
+    // None for events not triggered by a user
+    pub user_id: Option<i32>,
+    // only set for adjust_time events
+    pub time_delta_s: Option<f64>,
+    // duration of the heat, only set for start and reset_time events
+    pub duration_m: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeatTimeline {
+    pub heat_id: i32,
+    // when the heat was (last) started and stopped
+    pub start_datetime: Option<DateTime<Utc>>,
+    pub end_datetime: Option<DateTime<Utc>>,
+    pub events: Vec<HeatEvent>,
+}
+
+impl HeatEvent {
+    pub async fn find_by_heat_id<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: u32,
+    ) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, HeatEvent>(
+            r#"SELECT * FROM heat_events WHERE heat_id = $1 ORDER BY event_datetime, id"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn find_by_tournament_id(db: &Pool, tournament_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, HeatEvent>(
+            r#"
+SELECT e.*
+FROM heat_events e
+INNER JOIN heats h
+ON e.heat_id = h.id
+  INNER JOIN categories c
+  ON h.category_id = c.id
+WHERE c.tournament_id = $1
+ORDER BY e.heat_id, e.event_datetime, e.id
+        "#,
+        )
+        .bind(tournament_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    // events of all heats currently in the given state
+    pub async fn find_by_current_state(
+        db: &Pool,
+        state: HeatStateType,
+    ) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, HeatEvent>(
+            r#"
+SELECT e.*
+FROM heat_events e
+INNER JOIN current_heat_states s
+ON e.heat_id = s.heat_id
+WHERE s.state = $1
+ORDER BY e.heat_id, e.event_datetime, e.id
+        "#,
+        )
+        .bind(state)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn add<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: u32,
+        event: HeatEventType,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<Self> {
+        let query = r#"
+        INSERT INTO heat_events (heat_id, event, event_datetime, user_id)
+        VALUES ($1, $2, NOW(), $3)
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, HeatEvent>(query)
+            .bind(heat_id)
+            .bind(event)
+            .bind(user_id)
+            .fetch_one(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn add_time_adjustment<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: u32,
+        time_delta_s: f64,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<Self> {
+        let query = r#"
+        INSERT INTO heat_events (heat_id, event, event_datetime, user_id, time_delta_s)
+        VALUES ($1, $2, NOW(), $3, $4)
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, HeatEvent>(query)
+            .bind(heat_id)
+            .bind(HeatEventType::AdjustTime)
+            .bind(user_id)
+            .bind(time_delta_s)
+            .fetch_one(db)
+            .await?;
+        Ok(res)
+    }
+
+    // the heat gets its full duration (as configured now) again
+    pub async fn add_time_reset<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<Self> {
+        let query = r#"
+        INSERT INTO heat_events (heat_id, event, event_datetime, user_id, duration_m)
+        SELECT $1, $2, NOW(), $3, h.duration
+        FROM heats h
+        WHERE h.id = $1
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, HeatEvent>(query)
+            .bind(heat_id)
+            .bind(HeatEventType::ResetTime)
+            .bind(user_id)
+            .fetch_one(db)
+            .await?;
+        Ok(res)
+    }
+}
+
+impl HeatTimeline {
+    pub async fn by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Self> {
+        let events = HeatEvent::find_by_heat_id(db, heat_id).await?;
+        let current = HeatState::from_events(events.iter());
+        let start_datetime = current.as_ref().map(|s| s.start_datetime);
+        // a running heat has not ended yet
+        let end_datetime = current
+            .filter(|s| s.state == HeatStateType::Finished)
+            .map(|s| s.end_datetime);
+        Ok(HeatTimeline {
+            heat_id: heat_id as i32,
+            start_datetime,
+            end_datetime,
+            events,
+        })
+    }
+}
@@ -1,127 +1,324 @@
 use crate::database::Pool;
+use crate::models::{
+    area::AreaJudge,
+    armed_heat::ArmedHeat,
+    heat_event::{HeatEvent, HeatEventType},
+    priority::Priority,
+    score_lock::ScoreLock,
+};
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Type};
+use sqlx::{Postgres, Transaction, Type};
+use std::collections::BTreeMap;
 
-#[derive(Type, Debug, Serialize, Deserialize, Clone)]
+#[derive(Type, Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[sqlx(rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum HeatStateType {
+    // not started yet
+    Upcoming,
     Active,
     Paused,
-    Inactive,
+    Finished,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+impl HeatStateType {
+    // heats without a start event are upcoming
+    pub fn of(heat_state: Option<&HeatState>) -> Self {
+        heat_state
+            .map(|s| s.state.clone())
+            .unwrap_or(HeatStateType::Upcoming)
+    }
+}
+
+// current state of a started heat, derived from its events (it is not stored)
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HeatState {
     pub heat_id: i32,
-    pub start_datetime: NaiveDateTime,
-    pub end_datetime: NaiveDateTime,
-    pub pause_datetime: Option<NaiveDateTime>,
+    pub start_datetime: DateTime<Utc>,
+    pub end_datetime: DateTime<Utc>,
+    pub pause_datetime: Option<DateTime<Utc>>,
     pub remaining_time_s: Option<f64>,
     pub state: HeatStateType,
     pub duration_m: f64,
-    pub additional_data: Option<String>,
+}
+
+fn duration_s(seconds: f64) -> Duration {
+    Duration::milliseconds((seconds * 1000.0) as i64)
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
 }
 
 impl HeatState {
-    async fn find_option_bind(
-        db: &Pool,
-        query: &'static str,
-        value: u32,
-    ) -> anyhow::Result<Option<Self>> {
-        let res = sqlx::query_as::<_, HeatState>(query)
-            .bind(value)
-            .fetch_optional(db)
-            .await?
-            .map(|c| Self::from(c));
+    // replay the events of a heat (in order), None if the heat was never started
+    // events not changing the state they happen in (e.g. a pause of a paused heat) are ignored
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a HeatEvent>) -> Option<Self> {
+        let mut current: Option<HeatState> = None;
+        for event in events {
+            let now = event.event_datetime;
+            if event.event == HeatEventType::Start {
+                let duration_m = event.duration_m.unwrap_or(0.0);
+                current = Some(HeatState {
+                    heat_id: event.heat_id,
+                    start_datetime: now,
+                    end_datetime: now + duration_s(duration_m * 60.0),
+                    pause_datetime: None,
+                    remaining_time_s: None,
+                    state: HeatStateType::Active,
+                    duration_m,
+                });
+                continue;
+            }
+            let s = match current.as_mut() {
+                Some(s) => s,
+                None => continue,
+            };
+            let remaining_time_s = s.remaining_time_s.unwrap_or(0.0);
+            match (event.event, &s.state) {
+                (HeatEventType::Pause, HeatStateType::Active) => {
+                    s.remaining_time_s = Some(seconds(s.end_datetime - now).max(0.0));
+                    s.pause_datetime = Some(now);
+                    s.state = HeatStateType::Paused;
+                }
+                (HeatEventType::Unpause, HeatStateType::Paused) => {
+                    s.end_datetime = now + duration_s(remaining_time_s);
+                    s.pause_datetime = None;
+                    s.remaining_time_s = None;
+                    s.state = HeatStateType::Active;
+                }
+                (HeatEventType::ResetTime, HeatStateType::Active) => {
+                    s.duration_m = event.duration_m.unwrap_or(s.duration_m);
+                    s.end_datetime = now + duration_s(s.duration_m * 60.0);
+                }
+                (HeatEventType::ResetTime, HeatStateType::Paused) => {
+                    s.duration_m = event.duration_m.unwrap_or(s.duration_m);
+                    s.remaining_time_s = Some(s.duration_m * 60.0);
+                }
+                // a heat can not be shortened to less than no time left
+                (HeatEventType::AdjustTime, HeatStateType::Active) => {
+                    let time_delta_s = event.time_delta_s.unwrap_or(0.0);
+                    s.end_datetime = (s.end_datetime + duration_s(time_delta_s)).max(now);
+                }
+                (HeatEventType::AdjustTime, HeatStateType::Paused) => {
+                    let time_delta_s = event.time_delta_s.unwrap_or(0.0);
+                    s.remaining_time_s = Some((remaining_time_s + time_delta_s).max(0.0));
+                }
+                (HeatEventType::Stop, HeatStateType::Active | HeatStateType::Paused) => {
+                    s.end_datetime = now;
+                    s.pause_datetime = None;
+                    s.remaining_time_s = None;
+                    s.state = HeatStateType::Finished;
+                }
+                _ => (),
+            }
+        }
+        current
+    }
 
-        Ok(res)
+    // replay the events of several heats
+    fn from_events_by_heat(events: &[HeatEvent]) -> Vec<Self> {
+        let mut by_heat = BTreeMap::<i32, Vec<&HeatEvent>>::new();
+        for event in events.iter() {
+            by_heat.entry(event.heat_id).or_default().push(event);
+        }
+        by_heat
+            .values()
+            .filter_map(|events| Self::from_events(events.iter().copied()))
+            .collect()
     }
 
     pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Option<Self>> {
-        Self::find_option_bind(
-            &db,
-            r#"SELECT * FROM heat_state WHERE heat_id = $1"#,
-            heat_id,
-        )
-        .await
+        let events = HeatEvent::find_by_heat_id(db, heat_id).await?;
+        Ok(Self::from_events(events.iter()))
     }
 
-    pub async fn set_heat_started(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
-        let res = sqlx::query(
+    pub async fn find_by_tournament_id(db: &Pool, tournament_id: u32) -> anyhow::Result<Vec<Self>> {
+        let events = HeatEvent::find_by_tournament_id(db, tournament_id).await?;
+        Ok(Self::from_events_by_heat(&events))
+    }
+
+    // running heats (paused heats have no running clock)
+    pub async fn find_running(db: &Pool) -> anyhow::Result<Vec<Self>> {
+        let events = HeatEvent::find_by_current_state(db, HeatStateType::Active).await?;
+        Ok(Self::from_events_by_heat(&events))
+    }
+
+    // lock the heat against concurrent changes of its state and replay its events
+    // returns the time of the transaction and the current state, None if the heat was never started
+    async fn lock_current(
+        tx: &mut Transaction<'_, Postgres>,
+        heat_id: u32,
+    ) -> anyhow::Result<Option<(DateTime<Utc>, Self)>> {
+        let locked: Option<(DateTime<Utc>,)> =
+            sqlx::query_as(r#"SELECT NOW() FROM heats WHERE id = $1 FOR UPDATE"#)
+                .bind(heat_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let now = match locked {
+            Some((now,)) => now,
+            None => return Ok(None),
+        };
+        let events = HeatEvent::find_by_heat_id(&mut *tx, heat_id).await?;
+        Ok(Self::from_events(events.iter()).map(|s| (now, s)))
+    }
+
+    // record the event if the heat currently is in one of the given states
+    async fn change_state(
+        db: &Pool,
+        heat_id: u32,
+        from: &[HeatStateType],
+        event: HeatEventType,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        let changed = matches!(
+            Self::lock_current(&mut tx, heat_id).await?,
+            Some((_, current)) if from.contains(&current.state)
+        );
+        Self::log_event(tx, changed, heat_id, event, user_id).await
+    }
+
+    // a finished heat can be started again
+    // only one heat of an area may run (or be paused) at a time, no heat starts in a tournament on hold
+    pub async fn set_heat_started(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        // serialize concurrent starts of heats in the same area
+        sqlx::query(
             r#"
-INSERT INTO heat_state (heat_id, state, start_datetime, end_datetime, duration_m)
-SELECT $1, $2, NOW(), NOW() + heats.duration * interval '60 seconds', heats.duration
-FROM heats
-WHERE heats.id = $1
-ON CONFLICT (heat_id)
-DO NOTHING
-RETURNING heat_id;
+SELECT a.id
+FROM areas a
+INNER JOIN heats h
+ON h.area_id = a.id
+WHERE h.id = $1
+FOR UPDATE OF a;
         "#,
         )
         .bind(heat_id)
-        .bind(HeatStateType::Active)
-        .execute(db)
+        .execute(&mut tx)
         .await?;
-        Ok(res.rows_affected() > 0)
-    }
-
-    pub async fn set_heat_stopped(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
-        let res = sqlx::query(
+        sqlx::query(r#"SELECT id FROM heats WHERE id = $1 FOR UPDATE"#)
+            .bind(heat_id)
+            .execute(&mut tx)
+            .await?;
+        // a tournament is put on hold either before or after the start (see TournamentHold::hold)
+        sqlx::query(
             r#"
-DELETE FROM heat_state
-WHERE heat_id = $1
-RETURNING heat_id;
+SELECT t.id
+FROM tournaments t
+INNER JOIN categories c
+ON c.tournament_id = t.id
+  INNER JOIN heats h
+  ON h.category_id = c.id
+WHERE h.id = $1
+FOR SHARE OF t;
         "#,
         )
         .bind(heat_id)
-        .execute(db)
+        .execute(&mut tx)
         .await?;
-        Ok(res.rows_affected() > 0)
-    }
-
-    pub async fn set_heat_paused(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
         let res = sqlx::query(
             r#"
-UPDATE heat_state
-SET
-  pause_datetime = NOW(),
-  remaining_time_s = GREATEST(0, EXTRACT(EPOCH FROM (end_datetime - NOW()))),
-  state = $2
-WHERE heat_id = $1
-RETURNING heat_id;
+INSERT INTO heat_events (heat_id, event, event_datetime, user_id, duration_m)
+SELECT $1, $2, NOW(), $3, heats.duration
+FROM heats
+WHERE heats.id = $1
+  AND NOT EXISTS (
+    SELECT 1 FROM current_heat_states s
+    INNER JOIN heats h
+    ON s.heat_id = h.id
+    WHERE (h.id = $1 OR h.area_id = heats.area_id) AND s.state IN ('active', 'paused'))
+  AND NOT EXISTS (
+    SELECT 1 FROM tournament_holds th
+    INNER JOIN categories c
+    ON th.tournament_id = c.tournament_id
+    WHERE c.id = heats.category_id)
+RETURNING id;
         "#,
         )
         .bind(heat_id)
-        .bind(HeatStateType::Paused)
-        .execute(db)
+        .bind(HeatEventType::Start)
+        .bind(user_id)
+        .execute(&mut tx)
         .await?;
-        Ok(res.rows_affected() > 0)
+
+        // a restarted heat can be scored again and nobody has priority yet
+        let started = res.rows_affected() > 0;
+        if started {
+            AreaJudge::assign_to_heat(&mut tx, heat_id).await?;
+        }
+        tx.commit().await?;
+        if started {
+            ScoreLock::unlock(db, heat_id).await?;
+            Priority::reset(db, heat_id).await?;
+            // arming is for one (automatic) start only
+            ArmedHeat::disarm(db, heat_id).await?;
+        }
+        Ok(started)
     }
 
-    pub async fn set_heat_unpaused(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
-        let res = sqlx::query(
-            r#"
-UPDATE heat_state
-SET
-  pause_datetime = NULL,
-  remaining_time_s = NULL,
-  end_datetime = NOW() + remaining_time_s * interval '1 second',
-  state = $2
-WHERE heat_id = $1
-RETURNING heat_id;
-        "#,
+    // judges may still change their scores for lock_scores_after_s after the heat stopped
+    pub async fn set_heat_stopped(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+        lock_scores_after_s: f64,
+    ) -> anyhow::Result<bool> {
+        let stopped = Self::change_state(
+            db,
+            heat_id,
+            &[HeatStateType::Active, HeatStateType::Paused],
+            HeatEventType::Stop,
+            user_id,
         )
-        .bind(heat_id)
-        .bind(HeatStateType::Active)
-        .execute(db)
         .await?;
-        Ok(res.rows_affected() > 0)
+        if stopped {
+            ScoreLock::lock_delayed(db, heat_id, lock_scores_after_s).await?;
+        }
+        Ok(stopped)
     }
 
-    pub async fn toggle_heat_pause(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+    pub async fn set_heat_paused(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        Self::change_state(
+            db,
+            heat_id,
+            &[HeatStateType::Active],
+            HeatEventType::Pause,
+            user_id,
+        )
+        .await
+    }
+
+    pub async fn set_heat_unpaused(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        Self::change_state(
+            db,
+            heat_id,
+            &[HeatStateType::Paused],
+            HeatEventType::Unpause,
+            user_id,
+        )
+        .await
+    }
+
+    pub async fn toggle_heat_pause(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
         let heat_state = Self::find_by_heat_id(db, heat_id).await?;
         if heat_state.is_none() {
             return Ok(false);
@@ -129,27 +326,240 @@ RETURNING heat_id;
         let heat_state = heat_state.unwrap();
 
         match heat_state.state {
-            HeatStateType::Active => Self::set_heat_paused(db, heat_id).await,
-            HeatStateType::Paused => Self::set_heat_unpaused(db, heat_id).await,
+            HeatStateType::Active => Self::set_heat_paused(db, heat_id, user_id).await,
+            HeatStateType::Paused => Self::set_heat_unpaused(db, heat_id, user_id).await,
             _ => Ok(false),
         }
     }
 
-    pub async fn reset_heat_time(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
-        let res = sqlx::query(
-            r#"
-UPDATE heat_state hs
-SET
-  end_datetime = NOW() + h.duration * interval '60 seconds',
-  remaining_time_s = 60 * h.duration
-FROM heats h
-WHERE hs.heat_id = $1 AND h.id = $1
-RETURNING heat_id;
-        "#,
-        )
-        .bind(heat_id)
-        .execute(db)
-        .await?;
-        Ok(res.rows_affected() > 0)
+    pub async fn reset_heat_time(
+        db: &Pool,
+        heat_id: u32,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        let reset = matches!(
+            Self::lock_current(&mut tx, heat_id).await?,
+            Some((_, current)) if current.state == HeatStateType::Active
+                || current.state == HeatStateType::Paused
+        );
+        if reset {
+            HeatEvent::add_time_reset(&mut tx, heat_id, user_id).await?;
+        }
+        tx.commit().await?;
+        Ok(reset)
+    }
+
+    // add (or remove, for negative values) time to a running or paused heat
+    // a heat can not be shortened to less than no time left
+    pub async fn adjust_heat_time(
+        db: &Pool,
+        heat_id: u32,
+        time_delta_s: f64,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        // the applied delta differs from the requested one if the time was clamped at zero
+        let applied_delta_s = match Self::lock_current(&mut tx, heat_id).await? {
+            Some((now, current)) => match current.state {
+                HeatStateType::Active => {
+                    let end = (current.end_datetime + duration_s(time_delta_s)).max(now);
+                    Some(seconds(end - current.end_datetime))
+                }
+                HeatStateType::Paused => {
+                    let remaining_time_s = current.remaining_time_s.unwrap_or(0.0);
+                    Some((remaining_time_s + time_delta_s).max(0.0) - remaining_time_s)
+                }
+                _ => None,
+            },
+            None => None,
+        };
+
+        if let Some(applied_delta_s) = applied_delta_s {
+            HeatEvent::add_time_adjustment(&mut tx, heat_id, applied_delta_s, user_id).await?;
+        }
+        tx.commit().await?;
+        Ok(applied_delta_s.is_some())
+    }
+
+    // record the event of a state change (if there was a change) and commit it with the change
+    async fn log_event(
+        mut tx: Transaction<'_, Postgres>,
+        changed: bool,
+        heat_id: u32,
+        event: HeatEventType,
+        user_id: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        if changed {
+            HeatEvent::add(&mut tx, heat_id, event, user_id).await?;
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(second: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2021, 6, 1, 10, 0, 0).unwrap() + Duration::seconds(second)
+    }
+
+    fn event(event: HeatEventType, second: i64) -> HeatEvent {
+        HeatEvent {
+            id: 0,
+            heat_id: 1,
+            event,
+            event_datetime: at(second),
+            user_id: None,
+            time_delta_s: None,
+            duration_m: match event {
+                HeatEventType::Start | HeatEventType::ResetTime => Some(20.0),
+                _ => None,
+            },
+        }
+    }
+
+    fn adjust(second: i64, time_delta_s: f64) -> HeatEvent {
+        HeatEvent {
+            time_delta_s: Some(time_delta_s),
+            ..event(HeatEventType::AdjustTime, second)
+        }
+    }
+
+    #[test]
+    fn test_not_started() {
+        assert!(HeatState::from_events(&[]).is_none());
+        // events before the first start do not start the heat
+        let events = vec![event(HeatEventType::Pause, 0), adjust(10, 60.0)];
+        let state = HeatState::from_events(&events);
+        assert!(state.is_none());
+        assert_eq!(HeatStateType::of(state.as_ref()), HeatStateType::Upcoming);
+    }
+
+    #[test]
+    fn test_start() {
+        let state = HeatState::from_events(&[event(HeatEventType::Start, 0)]).unwrap();
+        assert_eq!(state.state, HeatStateType::Active);
+        assert_eq!(state.start_datetime, at(0));
+        assert_eq!(state.end_datetime, at(1200));
+        assert_eq!(state.duration_m, 20.0);
+    }
+
+    #[test]
+    fn test_pause_and_unpause() {
+        let mut events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::Pause, 300),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Paused);
+        assert_eq!(state.pause_datetime, Some(at(300)));
+        assert_eq!(state.remaining_time_s, Some(900.0));
+
+        // the remaining time runs from the unpause on
+        events.push(event(HeatEventType::Unpause, 400));
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Active);
+        assert_eq!(state.end_datetime, at(1300));
+        assert_eq!(state.pause_datetime, None);
+        assert_eq!(state.remaining_time_s, None);
+    }
+
+    #[test]
+    fn test_pause_after_time_is_up() {
+        let events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::Pause, 1500),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.remaining_time_s, Some(0.0));
+    }
+
+    #[test]
+    fn test_adjust_time() {
+        let events = vec![event(HeatEventType::Start, 0), adjust(100, 60.0)];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.end_datetime, at(1260));
+
+        // a running heat ends not before the adjustment
+        let events = vec![event(HeatEventType::Start, 0), adjust(100, -1500.0)];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.end_datetime, at(100));
+
+        let events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::Pause, 300),
+            adjust(310, -1000.0),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.remaining_time_s, Some(0.0));
+    }
+
+    #[test]
+    fn test_reset_time() {
+        let events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::ResetTime, 300),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.end_datetime, at(1500));
+
+        let events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::Pause, 300),
+            event(HeatEventType::ResetTime, 310),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Paused);
+        assert_eq!(state.remaining_time_s, Some(1200.0));
+    }
+
+    #[test]
+    fn test_stop_and_restart() {
+        let mut events = vec![
+            event(HeatEventType::Start, 0),
+            event(HeatEventType::Pause, 300),
+            event(HeatEventType::Stop, 400),
+        ];
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Finished);
+        assert_eq!(state.end_datetime, at(400));
+        assert_eq!(state.remaining_time_s, None);
+
+        // time changes of a finished heat are ignored
+        events.push(adjust(500, 60.0));
+        events.push(event(HeatEventType::Unpause, 510));
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Finished);
+        assert_eq!(state.end_datetime, at(400));
+
+        events.push(event(HeatEventType::Start, 600));
+        let state = HeatState::from_events(&events).unwrap();
+        assert_eq!(state.state, HeatStateType::Active);
+        assert_eq!(state.start_datetime, at(600));
+        assert_eq!(state.end_datetime, at(1800));
+    }
+
+    #[test]
+    fn test_events_by_heat() {
+        let events = vec![
+            event(HeatEventType::Start, 0),
+            HeatEvent {
+                heat_id: 2,
+                ..event(HeatEventType::Start, 100)
+            },
+            HeatEvent {
+                heat_id: 2,
+                ..event(HeatEventType::Stop, 200)
+            },
+        ];
+        let states = HeatState::from_events_by_heat(&events);
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].state, HeatStateType::Active);
+        assert_eq!(states[1].heat_id, 2);
+        assert_eq!(states[1].state, HeatStateType::Finished);
     }
 }
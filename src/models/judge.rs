@@ -1,7 +1,7 @@
 use crate::database::Pool;
 use crate::models::user::User;
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
 use futures::future;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -10,13 +10,13 @@ use sqlx::FromRow;
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct JudgingRequestCore {
     pub judge_id: i32,
-    pub expire_date: NaiveDateTime,
+    pub expire_date: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JudgingRequest {
     pub judge_id: i32,
-    pub expire_date: NaiveDateTime,
+    pub expire_date: DateTime<Utc>,
     pub judge: Option<User>,
 }
 
@@ -103,12 +103,30 @@ pub struct JudgingAssignment {
 }
 
 impl JudgingAssignment {
+    pub async fn is_assigned(db: &Pool, heat_id: u32, judge_id: u32) -> anyhow::Result<bool> {
+        let (assigned,): (bool,) = sqlx::query_as(
+            r#"
+SELECT EXISTS (
+  SELECT 1 FROM judge_assignments
+  WHERE heat_id = $1 AND judge_id = $2
+);
+        "#,
+        )
+        .bind(heat_id)
+        .bind(judge_id)
+        .fetch_one(db)
+        .await?;
+        Ok(assigned)
+    }
+
     pub async fn add(db: &Pool, heat_id: u32, judge_id: u32) -> anyhow::Result<bool> {
         let res = sqlx::query(
             r#"
 INSERT INTO judge_assignments (heat_id, judge_id)
 VALUES ($1, $2)
-ON CONFLICT (heat_id, judge_id) DO NOTHING;
+ON CONFLICT (heat_id, judge_id) DO UPDATE  -- a judge of the area's panel stays when the panel changes
+SET from_area = FALSE
+WHERE judge_assignments.from_area;
         "#,
         )
         .bind(heat_id)
@@ -132,3 +150,59 @@ WHERE heat_id = $1 AND judge_id = $2;
         Ok(res.rows_affected() > 0)
     }
 }
+
+// this struct represents a judge excluded from a heat's panel from a given wave on
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JudgeExclusion {
+    pub heat_id: i32,
+    pub judge_id: i32,
+    pub from_wave: i32,
+}
+
+impl JudgeExclusion {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, JudgeExclusion>(
+            r#"SELECT * FROM judge_exclusions WHERE heat_id = $1"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn add(
+        db: &Pool,
+        heat_id: u32,
+        judge_id: u32,
+        from_wave: i32,
+    ) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+INSERT INTO judge_exclusions (heat_id, judge_id, from_wave)
+VALUES ($1, $2, $3)
+ON CONFLICT (heat_id, judge_id) DO UPDATE
+SET from_wave = EXCLUDED.from_wave;
+        "#,
+        )
+        .bind(heat_id)
+        .bind(judge_id)
+        .bind(from_wave)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete(db: &Pool, heat_id: u32, judge_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM judge_exclusions
+WHERE heat_id = $1 AND judge_id = $2;
+        "#,
+        )
+        .bind(heat_id)
+        .bind(judge_id)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::configuration::CONFIG;
+use crate::database::Pool;
+use crate::models::{
+    category::Category, heat::Heat, judge::JudgeExclusion, preliminary_result::PreliminaryResult,
+    result::Result, score::Score, user::User,
+};
+use crate::score_computation::{
+    judge_analytics::{compute_judge_statistics, HeatJudging, JudgeStatistics},
+    JudgingPanel, ResultComputation,
+};
+
+struct HeatData {
+    heat: Heat,
+    judges: Vec<User>,
+    exclusions: Vec<JudgeExclusion>,
+    scores: Vec<Score>,
+    placings: Vec<Result>,
+    score_processor: Box<dyn ResultComputation>,
+}
+
+pub struct JudgeAnalytics {}
+
+impl JudgeAnalytics {
+    async fn load_heat(db: &Pool, heat: Heat) -> anyhow::Result<HeatData> {
+        let heat_id = heat.id as u32;
+        let judges = User::find_by_judge_assignments(db, heat_id, false).await?;
+        let exclusions = JudgeExclusion::find_by_heat_id(db, heat_id).await?;
+        let scores = Score::find_by_heat(db, heat_id).await?;
+
+        // compare with the published placing, or the computed one if nothing was published yet
+        let mut placings = Result::find_by_heat_id(db, heat_id, false).await?;
+        if placings.is_empty() {
+            placings = PreliminaryResult::by_heat_id(db, heat_id).await?;
+        }
+        let score_processor = PreliminaryResult::score_processor(db, &heat).await?;
+
+        Ok(HeatData {
+            heat,
+            judges,
+            exclusions,
+            scores,
+            placings,
+            score_processor,
+        })
+    }
+
+    async fn compute(db: &Pool, heats: Vec<Heat>) -> anyhow::Result<Vec<JudgeStatistics>> {
+        let mut heat_data = Vec::new();
+        for heat in heats.into_iter() {
+            heat_data.push(Self::load_heat(db, heat).await?);
+        }
+
+        let heat_judging: Vec<HeatJudging> = heat_data
+            .iter()
+            .map(|h| HeatJudging {
+                heat_id: h.heat.id,
+                panel: JudgingPanel {
+                    judges: &h.judges,
+                    exclusions: &h.exclusions,
+                    quorum: CONFIG.judging.quorum,
+                },
+                scores: &h.scores,
+                placings: &h.placings,
+                score_processor: h.score_processor.as_ref(),
+            })
+            .collect();
+
+        Ok(compute_judge_statistics(&heat_judging))
+    }
+
+    pub async fn by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<JudgeStatistics>> {
+        let heats = Heat::find_by_id(db, heat_id, false)
+            .await?
+            .into_iter()
+            .collect();
+        Self::compute(db, heats).await
+    }
+
+    pub async fn by_category_id(
+        db: &Pool,
+        category_id: u32,
+    ) -> anyhow::Result<Vec<JudgeStatistics>> {
+        let heats = Heat::find_by_category_id(db, category_id, false).await?;
+        Self::compute(db, heats).await
+    }
+
+    pub async fn by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+    ) -> anyhow::Result<Vec<JudgeStatistics>> {
+        let mut heats = Vec::new();
+        for category in Category::find_by_tournament_id(db, tournament_id, false).await? {
+            heats.extend(Heat::find_by_category_id(db, category.id as u32, false).await?);
+        }
+        Self::compute(db, heats).await
+    }
+}
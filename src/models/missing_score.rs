@@ -0,0 +1,28 @@
+use crate::configuration::CONFIG;
+use crate::database::Pool;
+use crate::models::judge::JudgeExclusion;
+use crate::models::score::Score;
+use crate::models::user::User;
+use crate::models::wave_log::LoggedWave;
+use crate::score_computation::{
+    missing_scores::{find_missing_scores, MissingScore},
+    JudgingPanel,
+};
+
+pub struct MissingScores {}
+
+impl MissingScores {
+    pub async fn by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<MissingScore>> {
+        let judges = User::find_by_judge_assignments(db, heat_id, false).await?;
+        let exclusions = JudgeExclusion::find_by_heat_id(db, heat_id).await?;
+        let scores = Score::find_by_heat(db, heat_id).await?;
+        let logged_waves = LoggedWave::find_by_heat_id(db, heat_id).await?;
+
+        let panel = JudgingPanel {
+            judges: &judges,
+            exclusions: &exclusions,
+            quorum: CONFIG.judging.quorum,
+        };
+        Ok(find_missing_scores(&panel, &scores, &logged_waves))
+    }
+}
@@ -1,14 +1,30 @@
+pub mod area;
+pub mod armed_heat;
 pub mod category;
 pub mod heat;
 pub mod heat_advancement;
+pub mod heat_event;
 pub mod heat_state;
 pub mod judge;
+pub mod judge_analytics;
 pub mod lycra_color;
+pub mod missing_score;
+pub mod needs;
 pub mod participation;
 pub mod permission;
 pub mod preliminary_result;
+pub mod priority;
 pub mod result;
+pub mod schedule;
 pub mod score;
+pub mod score_lock;
+pub mod score_override;
+pub mod score_submission;
 pub mod surfer;
+pub mod team;
+pub mod team_standing;
+pub mod tie_decision;
 pub mod tournament;
+pub mod tournament_hold;
 pub mod user;
+pub mod wave_log;
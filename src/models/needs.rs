@@ -0,0 +1,39 @@
+use crate::database::Pool;
+use crate::models::{
+    heat::{Heat, HeatType},
+    participation::Participation,
+    preliminary_result::PreliminaryResult,
+};
+use crate::score_computation::needs::{compute_needs, SurferNeeds};
+
+pub struct Needs {}
+
+impl Needs {
+    pub async fn by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<SurferNeeds>> {
+        let heat = Heat::find_by_id(db, heat_id, false).await?;
+        if heat.is_none() {
+            return Ok(Vec::new());
+        }
+        let heat = heat.unwrap();
+        // in call heats every wave is contested and any score wins it, a surfer can not
+        // improve by a given score on a wave of their own
+        if let HeatType::Call = heat.heat_type {
+            return Ok(Vec::new());
+        }
+
+        let surfer_ids: Vec<i32> = Participation::find_by_heat_id(db, heat_id, false)
+            .await?
+            .iter()
+            .map(|p| p.surfer_id)
+            .collect();
+        let results = PreliminaryResult::by_heat_id(db, heat_id).await?;
+        let score_processor = PreliminaryResult::score_processor(db, &heat).await?;
+
+        Ok(compute_needs(
+            heat_id as i32,
+            &surfer_ids,
+            &results,
+            score_processor.as_ref(),
+        ))
+    }
+}
@@ -14,6 +14,12 @@ pub enum PermissionType {
     #[serde(rename = "ac_commentator")]
     #[sqlx(rename = "ac_commentator")]
     Commentator,
+    #[serde(rename = "ac_headjudge")]
+    #[sqlx(rename = "ac_headjudge")]
+    HeadJudge,
+    #[serde(rename = "ac_spotter")]
+    #[sqlx(rename = "ac_spotter")]
+    Spotter,
 }
 
 // this struct will be used to represent database record
@@ -1,16 +1,39 @@
+use crate::configuration::CONFIG;
 use crate::database::Pool;
-use crate::models::heat::{Heat, HeatType};
+use crate::models::category::Category;
+use crate::models::heat::Heat;
+use crate::models::judge::JudgeExclusion;
 use crate::models::result::Result;
 use crate::models::score::Score;
+use crate::models::score_override::ScoreOverride;
+use crate::models::tie_decision::TieDecision;
 use crate::models::user::User;
-use crate::score_computation::{compute_results, default_heat::DefaultHeat, rsl_heat::RSLHeat};
+use crate::score_computation::{
+    compute_results, result_computation, JudgingPanel, ResultComputation,
+};
 
 pub struct PreliminaryResult {}
 
 impl PreliminaryResult {
+    // result computation of a heat with the tie break rule of its category
+    pub async fn score_processor(
+        db: &Pool,
+        heat: &Heat,
+    ) -> anyhow::Result<Box<dyn ResultComputation>> {
+        let category = Category::find_by_id(db, heat.category_id as u32, false).await?;
+        let tie_decisions = TieDecision::find_by_heat_id(db, heat.id as u32).await?;
+        Ok(result_computation(
+            &heat.heat_type,
+            category.and_then(|c| c.tie_break),
+            &tie_decisions,
+        ))
+    }
+
     pub async fn by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Result>> {
         let judges = User::find_by_judge_assignments(db, heat_id, false).await?;
+        let exclusions = JudgeExclusion::find_by_heat_id(db, heat_id).await?;
         let scores = Score::find_by_heat(db, heat_id).await?;
+        let overrides = ScoreOverride::find_by_heat_id(db, heat_id).await?;
         let heat = Heat::find_by_id(db, heat_id, false).await?;
         let results = Result::find_by_heat_id(db, heat_id, false).await?;
 
@@ -18,19 +41,21 @@ impl PreliminaryResult {
             return Ok(Vec::new());
         }
         let heat = heat.unwrap();
+        let score_processor = Self::score_processor(db, &heat).await?;
 
-        let results = match heat.heat_type {
-            HeatType::Standard => compute_results(
-                heat_id as i32,
-                &judges,
-                &scores,
-                &results,
-                &DefaultHeat::default(),
-            ),
-            HeatType::Call => {
-                compute_results(heat_id as i32, &judges, &scores, &results, &RSLHeat {})
-            }
+        let panel = JudgingPanel {
+            judges: &judges,
+            exclusions: &exclusions,
+            quorum: CONFIG.judging.quorum,
         };
+        let results = compute_results(
+            heat_id as i32,
+            &panel,
+            &scores,
+            &overrides,
+            &results,
+            score_processor.as_ref(),
+        );
 
         Ok(results)
     }
@@ -0,0 +1,131 @@
+use crate::database::Pool;
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Priority {
+    pub heat_id: i32,
+    pub surfer_id: i32,
+    pub priority: i32,
+}
+
+impl Priority {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, Priority>(
+            r#"SELECT * FROM heat_priorities WHERE heat_id = $1 ORDER BY priority"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    // replace the priority order of a heat, the first surfer has priority
+    pub async fn set(db: &Pool, heat_id: u32, surfer_ids: &[i32]) -> anyhow::Result<Vec<Self>> {
+        let mut tx = db.begin().await?;
+        sqlx::query(r#"DELETE FROM heat_priorities WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(&mut tx)
+            .await?;
+        for (priority, surfer_id) in surfer_ids.iter().enumerate() {
+            sqlx::query(
+                r#"
+INSERT INTO heat_priorities (heat_id, surfer_id, priority)
+SELECT p.heat_id, p.surfer_id, $3
+FROM participations p  -- only participating surfers get a priority
+WHERE p.heat_id = $1 AND p.surfer_id = $2;
+            "#,
+            )
+            .bind(heat_id)
+            .bind(surfer_id)
+            .bind(priority as i32)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_heat_id(db, heat_id).await
+    }
+
+    pub async fn reset(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(r#"DELETE FROM heat_priorities WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // a surfer that rode a wave moves to the end of the priority order,
+    // surfers that did not ride yet keep priority over the rider (in order of their seed)
+    pub async fn surfer_rode_wave(db: &Pool, heat_id: u32, surfer_id: u32) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        sqlx::query(
+            r#"
+INSERT INTO heat_priorities (heat_id, surfer_id, priority)
+SELECT p.heat_id, p.surfer_id, (
+  SELECT COALESCE(MAX(hp.priority) + 1, 0) FROM heat_priorities hp WHERE hp.heat_id = $1
+) + (ROW_NUMBER() OVER (ORDER BY p.seed, p.surfer_id))::INTEGER - 1
+FROM participations p
+WHERE p.heat_id = $1 AND p.surfer_id <> $2
+  AND NOT EXISTS (
+    SELECT 1 FROM heat_priorities hp
+    WHERE hp.heat_id = p.heat_id AND hp.surfer_id = p.surfer_id
+  );
+        "#,
+        )
+        .bind(heat_id)
+        .bind(surfer_id)
+        .execute(&mut tx)
+        .await?;
+        let res = sqlx::query(
+            r#"
+INSERT INTO heat_priorities (heat_id, surfer_id, priority)
+SELECT p.heat_id, p.surfer_id, (
+  SELECT COALESCE(MAX(hp.priority) + 1, 0) FROM heat_priorities hp WHERE hp.heat_id = $1
+)
+FROM participations p
+WHERE p.heat_id = $1 AND p.surfer_id = $2
+ON CONFLICT (heat_id, surfer_id) DO UPDATE
+SET priority = EXCLUDED.priority;
+        "#,
+        )
+        .bind(heat_id)
+        .bind(surfer_id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // rebuild the priority order from the logged waves (after a logged wave was deleted),
+    // surfers that did not ride yet come first, then in order of their last wave
+    pub async fn from_wave_log(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let mut tx = db.begin().await?;
+        sqlx::query(r#"DELETE FROM heat_priorities WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(&mut tx)
+            .await?;
+        let res = sqlx::query(
+            r#"
+INSERT INTO heat_priorities (heat_id, surfer_id, priority)
+SELECT p.heat_id, p.surfer_id,
+  (ROW_NUMBER() OVER (ORDER BY w.last_logged_datetime NULLS FIRST, p.seed, p.surfer_id))::INTEGER - 1
+FROM participations p
+LEFT JOIN (
+  SELECT surfer_id, MAX(logged_datetime) AS last_logged_datetime
+  FROM wave_log WHERE heat_id = $1
+  GROUP BY surfer_id
+) w
+ON w.surfer_id = p.surfer_id
+WHERE p.heat_id = $1
+  AND EXISTS (SELECT 1 FROM wave_log wl WHERE wl.heat_id = $1);
+        "#,
+        )
+        .bind(heat_id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -1,24 +1,33 @@
 use crate::database::Pool;
-use crate::models::{heat::Heat, surfer::Surfer};
+use crate::models::{heat::Heat, score::ScoreKind, surfer::Surfer};
+use crate::score_computation::Points;
 
 use futures::future;
 
 use serde::{Deserialize, Serialize};
-use sqlx::{types::Json, FromRow};
+use sqlx::{types::Json, FromRow, Type};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WaveScoreCore {
     pub surfer_id: i32,
     pub wave: i32,
-    pub score: f64,
+    pub score: Points,
+    #[serde(default)]
+    pub kind: ScoreKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WaveScore {
     pub surfer_id: i32,
     pub wave: i32,
-    pub score: f64,
+    pub score: Points,
     pub published: bool,
+    #[serde(default)]
+    pub reduced_panel: bool,
+    #[serde(default)]
+    pub overridden: bool,
+    #[serde(default)]
+    pub kind: ScoreKind,
 }
 
 impl From<WaveScoreCore> for WaveScore {
@@ -28,16 +37,33 @@ impl From<WaveScoreCore> for WaveScore {
             wave: wave_score.wave,
             score: wave_score.score,
             published: true,
+            reduced_panel: false,
+            overridden: false,
+            kind: wave_score.kind,
         }
     }
 }
 
+// rule that decided the order of surfers with the same total score
+#[derive(Type, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    // the best of the waves that do not count for the total, then the next best and so on
+    RemainingWaves,
+    HighestSingleWave,
+    Countback,
+    // the tie is flagged to be resolved by the head judge
+    HeadJudgeDecision,
+    SharedPlace,
+}
+
 // this struct will be used to represent database record
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct ResultCore {
     pub heat_id: i32,
     pub surfer_id: i32,
-    pub total_score: f64,
+    pub total_score: Points,
     pub place: i32,
     pub wave_scores: Json<Vec<WaveScoreCore>>,
 }
@@ -47,10 +73,13 @@ pub struct ResultCore {
 pub struct Result {
     pub heat_id: i32,
     pub surfer_id: i32,
-    pub total_score: f64,
+    pub total_score: Points,
     pub place: i32,
     pub wave_scores: Vec<WaveScore>,
     pub published: bool,
+    // only set for surfers with the same total score as another surfer
+    #[serde(default)]
+    pub tie_break: Option<TieBreak>,
     pub heat: Option<Heat>,
     pub surfer: Option<Surfer>,
 }
@@ -64,6 +93,7 @@ impl From<ResultCore> for Result {
             place: result.place,
             wave_scores: result.wave_scores.0.into_iter().map(|s| s.into()).collect(),
             published: true,
+            tie_break: None,
             heat: None,
             surfer: None,
         }
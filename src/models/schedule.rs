@@ -0,0 +1,363 @@
+use crate::database::Pool;
+use crate::models::{
+    armed_heat::ArmedHeat,
+    heat::Heat,
+    heat_state::{HeatState, HeatStateType},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScheduledHeat {
+    pub heat_id: i32,
+    pub category_id: i32,
+    pub area_id: Option<i32>,
+    pub name: String,
+    pub state: HeatStateType,
+    pub planned_start_datetime: DateTime<Utc>,
+    pub planned_end_datetime: DateTime<Utc>,
+    // actual times for heats that started already
+    pub estimated_start_datetime: DateTime<Utc>,
+    pub estimated_end_datetime: DateTime<Utc>,
+    pub delay_s: f64,
+}
+
+pub struct Schedule {}
+
+impl Schedule {
+    pub async fn by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+    ) -> anyhow::Result<Vec<ScheduledHeat>> {
+        let heats = Heat::find_by_tournament_id(db, tournament_id, false).await?;
+        let heat_states = HeatState::find_by_tournament_id(db, tournament_id).await?;
+        Ok(estimate_schedule(&heats, &heat_states, Utc::now()))
+    }
+
+    // armed heats of a tournament that are due to be started now
+    pub async fn due_heats_by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+    ) -> anyhow::Result<Vec<i32>> {
+        let armed: HashSet<i32> = ArmedHeat::find_by_tournament_id(db, tournament_id)
+            .await?
+            .iter()
+            .map(|a| a.heat_id)
+            .collect();
+        if armed.is_empty() {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+        let heats = Heat::find_by_tournament_id(db, tournament_id, false).await?;
+        let heat_states = HeatState::find_by_tournament_id(db, tournament_id).await?;
+        let schedule = estimate_schedule(&heats, &heat_states, now);
+        Ok(due_heats(&schedule, &armed, now))
+    }
+
+    pub async fn tournament_id_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Option<i32>> {
+        let res: Option<(i32,)> = sqlx::query_as(
+            r#"
+SELECT c.tournament_id
+FROM heats h
+INNER JOIN categories c
+ON h.category_id = c.id
+WHERE h.id = $1
+        "#,
+        )
+        .bind(heat_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(res.map(|(tournament_id,)| tournament_id))
+    }
+}
+
+fn duration_s(seconds: f64) -> Duration {
+    Duration::milliseconds((seconds * 1000.0) as i64)
+}
+
+// heats are expected in the order of their planned start
+// the delay of a heat (compared to its planned end) is passed on to all later heats of its area,
+// heats running ahead of time do not move later heats to an earlier time
+pub fn estimate_schedule(
+    heats: &[Heat],
+    heat_states: &[HeatState],
+    now: DateTime<Utc>,
+) -> Vec<ScheduledHeat> {
+    let states: HashMap<i32, &HeatState> = heat_states.iter().map(|s| (s.heat_id, s)).collect();
+
+    let mut delays = HashMap::<Option<i32>, Duration>::new();
+    let mut schedule = Vec::with_capacity(heats.len());
+    for heat in heats.iter() {
+        let planned_start = heat.start_datetime;
+        let planned_end = planned_start + duration_s(heat.duration * 60.0);
+
+        let (state, estimated_start, estimated_end) = match states.get(&heat.id) {
+            Some(heat_state) => {
+                let end = match heat_state.state {
+                    HeatStateType::Paused => {
+                        now + duration_s(heat_state.remaining_time_s.unwrap_or(0.0).max(0.0))
+                    }
+                    // a heat running over time ends not earlier than now
+                    HeatStateType::Active => heat_state.end_datetime.max(now),
+                    _ => heat_state.end_datetime,
+                };
+                (heat_state.state.clone(), heat_state.start_datetime, end)
+            }
+            None => {
+                // a heat that should have started already starts now at the earliest
+                let delay = delays
+                    .get(&heat.area_id)
+                    .copied()
+                    .unwrap_or_else(Duration::zero);
+                let start = (planned_start + delay).max(now);
+                (
+                    HeatStateType::Upcoming,
+                    start,
+                    start + duration_s(heat.duration * 60.0),
+                )
+            }
+        };
+
+        delays.insert(
+            heat.area_id,
+            (estimated_end - planned_end).max(Duration::zero()),
+        );
+        schedule.push(ScheduledHeat {
+            heat_id: heat.id,
+            category_id: heat.category_id,
+            area_id: heat.area_id,
+            name: heat.name.clone(),
+            state,
+            planned_start_datetime: planned_start,
+            planned_end_datetime: planned_end,
+            estimated_start_datetime: estimated_start,
+            estimated_end_datetime: estimated_end,
+            delay_s: (estimated_start - planned_start).num_milliseconds() as f64 / 1000.0,
+        });
+    }
+    schedule
+}
+
+// armed heats that are upcoming, reached their estimated start
+// and follow a finished heat in their area
+pub fn due_heats(schedule: &[ScheduledHeat], armed: &HashSet<i32>, now: DateTime<Utc>) -> Vec<i32> {
+    let mut previous_finished = HashMap::<Option<i32>, bool>::new();
+    let mut due = Vec::new();
+    for heat in schedule.iter() {
+        let upcoming = matches!(heat.state, HeatStateType::Upcoming);
+        if upcoming
+            && *previous_finished.get(&heat.area_id).unwrap_or(&true)
+            && armed.contains(&heat.heat_id)
+            && heat.estimated_start_datetime <= now
+        {
+            due.push(heat.heat_id);
+        }
+        previous_finished.insert(heat.area_id, matches!(heat.state, HeatStateType::Finished));
+    }
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::heat::HeatType;
+    use chrono::TimeZone;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 8, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    // heat of 20 minutes planned to start at the given minute
+    fn heat(id: i32, area_id: Option<i32>, start_minute: i64) -> Heat {
+        Heat {
+            id,
+            category_id: 1,
+            name: format!("Heat {}", id),
+            round: 0,
+            number_in_round: id,
+            start_datetime: at(start_minute),
+            number_of_waves: 10,
+            duration: 20.0,
+            heat_type: HeatType::Standard,
+            additional_info: None,
+            area_id,
+            participations: None,
+        }
+    }
+
+    fn heat_state(
+        heat_id: i32,
+        state: HeatStateType,
+        start_minute: i64,
+        end_minute: i64,
+        remaining_time_s: Option<f64>,
+    ) -> HeatState {
+        HeatState {
+            heat_id,
+            start_datetime: at(start_minute),
+            end_datetime: at(end_minute),
+            pause_datetime: None,
+            remaining_time_s,
+            state,
+            duration_m: 20.0,
+        }
+    }
+
+    // (heat_id, estimated start, estimated end) in minutes
+    fn estimates(schedule: &[ScheduledHeat]) -> Vec<(i32, i64, i64)> {
+        schedule
+            .iter()
+            .map(|h| {
+                (
+                    h.heat_id,
+                    (h.estimated_start_datetime - at(0)).num_minutes(),
+                    (h.estimated_end_datetime - at(0)).num_minutes(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_planned_times_without_delays() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20), heat(3, None, 40)];
+        let schedule = estimate_schedule(&heats, &[], at(-30));
+        assert_eq!(
+            estimates(&schedule),
+            vec![(1, 0, 20), (2, 20, 40), (3, 40, 60)]
+        );
+        assert!(schedule.iter().all(|h| h.delay_s == 0.0));
+    }
+
+    #[test]
+    fn late_start_delays_later_heats() {
+        // the first heat started 5 minutes late
+        let heats = vec![heat(1, None, 0), heat(2, None, 20), heat(3, None, 40)];
+        let states = vec![heat_state(1, HeatStateType::Active, 5, 25, None)];
+        let schedule = estimate_schedule(&heats, &states, at(10));
+        assert_eq!(
+            estimates(&schedule),
+            vec![(1, 5, 25), (2, 25, 45), (3, 45, 65)]
+        );
+        assert_eq!(schedule[2].delay_s, 300.0);
+    }
+
+    #[test]
+    fn overrunning_heat_ends_not_before_now() {
+        // the first heat should have ended at minute 20 but is still running
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Active, 0, 20, None)];
+        let schedule = estimate_schedule(&heats, &states, at(23));
+        assert_eq!(estimates(&schedule), vec![(1, 0, 23), (2, 23, 43)]);
+    }
+
+    #[test]
+    fn heat_finishing_early_does_not_move_later_heats_forward() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Finished, 0, 15, None)];
+        let schedule = estimate_schedule(&heats, &states, at(16));
+        assert_eq!(estimates(&schedule), vec![(1, 0, 15), (2, 20, 40)]);
+    }
+
+    #[test]
+    fn paused_heat_ends_after_remaining_time() {
+        // paused at minute 12 with 8 minutes left, now is minute 15
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(
+            1,
+            HeatStateType::Paused,
+            0,
+            20,
+            Some(8.0 * 60.0),
+        )];
+        let schedule = estimate_schedule(&heats, &states, at(15));
+        assert_eq!(estimates(&schedule), vec![(1, 0, 23), (2, 23, 43)]);
+    }
+
+    #[test]
+    fn upcoming_heat_starts_now_at_the_earliest() {
+        let heats = vec![heat(1, None, 0)];
+        let schedule = estimate_schedule(&heats, &[], at(7));
+        assert_eq!(estimates(&schedule), vec![(1, 7, 27)]);
+        assert!(matches!(schedule[0].state, HeatStateType::Upcoming));
+    }
+
+    fn armed(heat_ids: &[i32]) -> HashSet<i32> {
+        heat_ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn armed_heat_is_due_at_its_start() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let schedule = estimate_schedule(&heats, &[], at(-1));
+        assert!(due_heats(&schedule, &armed(&[1, 2]), at(-1)).is_empty());
+        let schedule = estimate_schedule(&heats, &[], at(0));
+        assert_eq!(due_heats(&schedule, &armed(&[1, 2]), at(0)), vec![1]);
+    }
+
+    #[test]
+    fn heat_that_is_not_armed_is_not_due() {
+        let heats = vec![heat(1, None, 0)];
+        let schedule = estimate_schedule(&heats, &[], at(5));
+        assert!(due_heats(&schedule, &armed(&[]), at(5)).is_empty());
+    }
+
+    #[test]
+    fn heat_after_a_late_start_is_due_later() {
+        // the first heat started 5 minutes late and finished on time
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Finished, 5, 25, None)];
+        let schedule = estimate_schedule(&heats, &states, at(24));
+        assert!(due_heats(&schedule, &armed(&[2]), at(24)).is_empty());
+        let schedule = estimate_schedule(&heats, &states, at(25));
+        assert_eq!(due_heats(&schedule, &armed(&[2]), at(25)), vec![2]);
+    }
+
+    #[test]
+    fn heat_is_not_due_while_previous_heat_overruns() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Active, 0, 20, None)];
+        let schedule = estimate_schedule(&heats, &states, at(22));
+        assert!(due_heats(&schedule, &armed(&[2]), at(22)).is_empty());
+    }
+
+    #[test]
+    fn heat_is_not_due_while_previous_heat_is_paused() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Paused, 0, 20, Some(0.0))];
+        let schedule = estimate_schedule(&heats, &states, at(30));
+        assert!(due_heats(&schedule, &armed(&[2]), at(30)).is_empty());
+    }
+
+    #[test]
+    fn heat_after_an_early_finish_waits_for_its_planned_start() {
+        let heats = vec![heat(1, None, 0), heat(2, None, 20)];
+        let states = vec![heat_state(1, HeatStateType::Finished, 0, 15, None)];
+        let schedule = estimate_schedule(&heats, &states, at(16));
+        assert!(due_heats(&schedule, &armed(&[2]), at(16)).is_empty());
+        let schedule = estimate_schedule(&heats, &states, at(20));
+        assert_eq!(due_heats(&schedule, &armed(&[2]), at(20)), vec![2]);
+    }
+
+    #[test]
+    fn areas_are_scheduled_independently() {
+        // the heat in area 1 overruns, the heat in area 2 finished on time
+        let heats = vec![
+            heat(1, Some(1), 0),
+            heat(2, Some(2), 0),
+            heat(3, Some(1), 20),
+            heat(4, Some(2), 20),
+        ];
+        let states = vec![
+            heat_state(1, HeatStateType::Active, 0, 20, None),
+            heat_state(2, HeatStateType::Finished, 0, 20, None),
+        ];
+        let schedule = estimate_schedule(&heats, &states, at(25));
+        assert_eq!(
+            estimates(&schedule),
+            vec![(1, 0, 25), (2, 0, 20), (3, 25, 45), (4, 25, 45)]
+        );
+        assert_eq!(due_heats(&schedule, &armed(&[3, 4]), at(25)), vec![4]);
+    }
+}
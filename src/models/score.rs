@@ -1,7 +1,17 @@
 use crate::database::Pool;
+use crate::score_computation::Points;
 
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgExecutor, Type};
+
+#[derive(Type, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreKind {
+    #[default]
+    Wave,
+    Jump,
+}
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Score {
@@ -9,9 +19,15 @@ pub struct Score {
     pub judge_id: i32,
     pub heat_id: i32,
     pub wave: i32,
-    pub score: f64,
+    pub score: Points,
     pub interference: bool,
     pub missed: bool,
+    #[serde(default)]
+    pub kind: ScoreKind,
+    // version of the score the change is based on (new scores start with version 1),
+    // only new scores may be added without a version
+    #[serde(default)]
+    pub version: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +36,8 @@ pub struct DeleteScore {
     pub judge_id: i32,
     pub heat_id: i32,
     pub wave: i32,
+    #[serde(default)]
+    pub version: Option<i32>,
 }
 
 impl Score {
@@ -58,19 +76,55 @@ impl Score {
         Ok(res)
     }
 
-    pub async fn add(db: &Pool, score: &Score) -> anyhow::Result<Option<Score>> {
+    pub async fn find_one<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: i32,
+        judge_id: i32,
+        surfer_id: i32,
+        wave: i32,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+            SELECT * FROM scores s
+            WHERE s.heat_id = $1 AND s.judge_id = $2 AND s.surfer_id = $3 AND s.wave = $4
+        "#;
+        let res = sqlx::query_as::<_, Score>(query)
+            .bind(heat_id)
+            .bind(judge_id)
+            .bind(surfer_id)
+            .bind(wave)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn add<'e, E: PgExecutor<'e>>(
+        db: E,
+        score: &Score,
+        ignore_lock: bool,
+    ) -> anyhow::Result<Option<Score>> {
         let query = r#"
-        INSERT INTO scores (heat_id, judge_id, surfer_id, wave, score, missed, interference)
-        (SELECT ja.heat_id, ja.judge_id, p.surfer_id, $4, $5, $6, $7
+        INSERT INTO scores (heat_id, judge_id, surfer_id, wave, score, missed, interference, kind, version)
+        (SELECT ja.heat_id, ja.judge_id, p.surfer_id, $4, $5, $6, $7, $9, 1
         FROM judge_assignments ja    -- only scores by assigned judges
         INNER JOIN participations p  -- only scores for participating surfers
         ON ja.heat_id = p.heat_id
-        WHERE ja.heat_id = $1 AND ja.judge_id = $2 AND p.surfer_id = $3)
+        WHERE ja.heat_id = $1 AND ja.judge_id = $2 AND p.surfer_id = $3
+          AND ($8 OR NOT EXISTS (    -- only scores for unlocked heats
+            SELECT 1 FROM heat_score_locks l
+            WHERE l.heat_id = ja.heat_id AND l.locked_datetime <= NOW()))
+          AND (NOT EXISTS (          -- only logged waves (if waves are logged for the heat)
+            SELECT 1 FROM wave_log w WHERE w.heat_id = ja.heat_id)
+            OR EXISTS (
+            SELECT 1 FROM wave_log w
+            WHERE w.heat_id = ja.heat_id AND w.surfer_id = p.surfer_id AND w.wave = $4)))
         ON CONFLICT (heat_id, surfer_id, judge_id, wave) DO UPDATE
         SET
           score = EXCLUDED.score,
           missed = EXCLUDED.missed,
-          interference = EXCLUDED.interference
+          interference = EXCLUDED.interference,
+          kind = EXCLUDED.kind,
+          version = scores.version + 1
+        WHERE scores.version = $10  -- only updates of the current version
         RETURNING *
         "#;
         let res = sqlx::query_as::<_, Score>(query)
@@ -81,15 +135,26 @@ impl Score {
             .bind(score.score)
             .bind(score.missed)
             .bind(score.interference)
+            .bind(ignore_lock)
+            .bind(score.kind)
+            .bind(score.version)
             .fetch_optional(db)
             .await?;
         Ok(res)
     }
 
-    pub async fn delete(db: &Pool, score: &DeleteScore) -> anyhow::Result<Option<Score>> {
+    pub async fn delete<'e, E: PgExecutor<'e>>(
+        db: E,
+        score: &DeleteScore,
+        ignore_lock: bool,
+    ) -> anyhow::Result<Option<Score>> {
         let query = r#"
         DELETE FROM scores s
         WHERE s.heat_id = $1 AND s.judge_id = $2 AND s.surfer_id = $3 AND s.wave = $4
+          AND ($5 OR NOT EXISTS (    -- only scores for unlocked heats
+            SELECT 1 FROM heat_score_locks l
+            WHERE l.heat_id = s.heat_id AND l.locked_datetime <= NOW()))
+          AND s.version = $6         -- only deletes of the current version
         RETURNING *
         "#;
         let res = sqlx::query_as::<_, Score>(query)
@@ -97,6 +162,8 @@ impl Score {
             .bind(score.judge_id)
             .bind(score.surfer_id)
             .bind(score.wave)
+            .bind(ignore_lock)
+            .bind(score.version)
             .fetch_optional(db)
             .await?;
         Ok(res)
@@ -0,0 +1,90 @@
+use crate::database::Pool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ScoreLock {
+    pub heat_id: i32,
+    pub locked_datetime: DateTime<Utc>,
+    pub locked_by: Option<i32>,
+    // computed, a lock with a grace period is not in effect yet
+    pub locked: bool,
+}
+
+impl ScoreLock {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, ScoreLock>(
+            r#"SELECT *, locked_datetime <= NOW() AS locked FROM heat_score_locks WHERE heat_id = $1"#,
+        )
+        .bind(heat_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn is_locked(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let (locked,): (bool,) = sqlx::query_as(
+            r#"
+SELECT EXISTS (
+  SELECT 1 FROM heat_score_locks
+  WHERE heat_id = $1 AND locked_datetime <= NOW()
+);
+        "#,
+        )
+        .bind(heat_id)
+        .fetch_one(db)
+        .await?;
+        Ok(locked)
+    }
+
+    pub async fn lock(db: &Pool, heat_id: u32, user_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+INSERT INTO heat_score_locks (heat_id, locked_datetime, locked_by)
+VALUES ($1, NOW(), $2)
+ON CONFLICT (heat_id) DO UPDATE
+SET
+  locked_datetime = EXCLUDED.locked_datetime,
+  locked_by = EXCLUDED.locked_by;
+        "#,
+        )
+        .bind(heat_id)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // lock scores after a grace period, unless they are already locked earlier
+    pub async fn lock_delayed(db: &Pool, heat_id: u32, delay_s: f64) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+INSERT INTO heat_score_locks (heat_id, locked_datetime)
+VALUES ($1, NOW() + $2 * interval '1 second')
+ON CONFLICT (heat_id) DO UPDATE
+SET locked_datetime = LEAST(heat_score_locks.locked_datetime, EXCLUDED.locked_datetime);
+        "#,
+        )
+        .bind(heat_id)
+        .bind(delay_s)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn unlock(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM heat_score_locks
+WHERE heat_id = $1;
+        "#,
+        )
+        .bind(heat_id)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
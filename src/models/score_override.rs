@@ -0,0 +1,94 @@
+use crate::database::Pool;
+use crate::score_computation::Points;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// this struct will be used to represent database record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScoreOverride {
+    pub heat_id: i32,
+    pub surfer_id: i32,
+    pub wave: i32,
+    pub score: Option<Points>,
+    pub voided: bool,
+    pub reason: String,
+    pub head_judge_id: Option<i32>,
+    pub override_datetime: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewScoreOverride {
+    pub surfer_id: i32,
+    pub wave: i32,
+    pub score: Option<Points>,
+    #[serde(default)]
+    pub voided: bool,
+    pub reason: String,
+}
+
+impl ScoreOverride {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, ScoreOverride>(
+            r#"SELECT * FROM score_overrides WHERE heat_id = $1"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn add(
+        db: &Pool,
+        heat_id: u32,
+        head_judge_id: u32,
+        score_override: &NewScoreOverride,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        INSERT INTO score_overrides (heat_id, surfer_id, wave, score, voided, reason, head_judge_id, override_datetime)
+        (SELECT p.heat_id, p.surfer_id, $3, $4, $5, $6, $7, NOW()
+        FROM participations p  -- only overrides for participating surfers
+        WHERE p.heat_id = $1 AND p.surfer_id = $2)
+        ON CONFLICT (heat_id, surfer_id, wave) DO UPDATE
+        SET
+          score = EXCLUDED.score,
+          voided = EXCLUDED.voided,
+          reason = EXCLUDED.reason,
+          head_judge_id = EXCLUDED.head_judge_id,
+          override_datetime = EXCLUDED.override_datetime
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, ScoreOverride>(query)
+            .bind(heat_id)
+            .bind(score_override.surfer_id)
+            .bind(score_override.wave)
+            .bind(score_override.score)
+            .bind(score_override.voided)
+            .bind(&score_override.reason)
+            .bind(head_judge_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn delete(
+        db: &Pool,
+        heat_id: u32,
+        surfer_id: u32,
+        wave: i32,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        DELETE FROM score_overrides
+        WHERE heat_id = $1 AND surfer_id = $2 AND wave = $3
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, ScoreOverride>(query)
+            .bind(heat_id)
+            .bind(surfer_id)
+            .bind(wave)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::models::score::{DeleteScore, Score};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum ScoreOperation {
+    Upsert(Score),
+    Delete(DeleteScore),
+}
+
+// one score change recorded by a judging tablet (possibly while offline)
+#[derive(Debug, Deserialize)]
+pub struct ScoreBatchItem {
+    pub idempotency_key: String,
+    pub client_datetime: DateTime<Utc>,
+    pub operation: ScoreOperation,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreBatchStatus {
+    Applied,
+    // the item has already been applied by an earlier submission
+    Duplicate,
+    // the item is based on an outdated version of the score (the current score is returned)
+    Conflict,
+    Rejected,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreBatchOutcome {
+    pub idempotency_key: String,
+    pub status: ScoreBatchStatus,
+    pub score: Option<Score>,
+    pub error: Option<String>,
+}
+
+impl ScoreBatchOutcome {
+    pub fn rejected(idempotency_key: &str, error: String) -> Self {
+        ScoreBatchOutcome {
+            idempotency_key: idempotency_key.to_string(),
+            status: ScoreBatchStatus::Rejected,
+            score: None,
+            error: Some(error),
+        }
+    }
+}
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ScoreSubmission {
+    pub judge_id: i32,
+    pub idempotency_key: String,
+    pub heat_id: i32,
+    pub client_datetime: DateTime<Utc>,
+    pub submitted_datetime: DateTime<Utc>,
+}
+
+impl ScoreSubmission {
+    // record the idempotency key of an item before applying it,
+    // None if the key was already recorded (by an earlier or a concurrent submission)
+    pub async fn claim<'e, E: PgExecutor<'e>>(
+        db: E,
+        judge_id: u32,
+        heat_id: u32,
+        item: &ScoreBatchItem,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        INSERT INTO score_submissions (judge_id, idempotency_key, heat_id, client_datetime)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (judge_id, idempotency_key) DO NOTHING
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, ScoreSubmission>(query)
+            .bind(judge_id)
+            .bind(&item.idempotency_key)
+            .bind(heat_id)
+            .bind(item.client_datetime)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    // forget the idempotency key of an item that was not applied, so it can be submitted again
+    pub async fn release<'e, E: PgExecutor<'e>>(
+        db: E,
+        judge_id: u32,
+        idempotency_key: &str,
+    ) -> anyhow::Result<bool> {
+        let res = sqlx::query(
+            r#"DELETE FROM score_submissions WHERE judge_id = $1 AND idempotency_key = $2"#,
+        )
+        .bind(judge_id)
+        .bind(idempotency_key)
+        .execute(db)
+        .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
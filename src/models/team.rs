@@ -0,0 +1,179 @@
+use crate::database::Pool;
+use crate::models::surfer::Surfer;
+
+use futures::future;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
+
+// how the heat results of team members are aggregated in a team category
+#[derive(Type, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TeamScoring {
+    PlacingPoints,
+    BestWaves,
+}
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TeamCore {
+    pub id: i32,
+    pub tournament_id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Team {
+    pub id: i32,
+    pub tournament_id: i32,
+    pub name: String,
+    pub members: Option<Vec<Surfer>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTeam {
+    pub name: String,
+}
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TeamMember {
+    pub team_id: i32,
+    pub surfer_id: i32,
+}
+
+impl From<TeamCore> for Team {
+    fn from(team: TeamCore) -> Team {
+        Team {
+            id: team.id,
+            tournament_id: team.tournament_id,
+            name: team.name,
+            members: None,
+        }
+    }
+}
+
+impl Team {
+    async fn expand(mut self, db: &Pool) -> Self {
+        self.members = Self::find_members(db, self.id as u32).await.ok();
+        self
+    }
+
+    pub async fn find_by_id(db: &Pool, team_id: u32, expand: bool) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, TeamCore>(r#"SELECT * FROM teams WHERE id = $1"#)
+            .bind(team_id)
+            .fetch_optional(db)
+            .await?
+            .map(Self::from);
+        Ok(match (res, expand) {
+            (Some(team), true) => Some(team.expand(db).await),
+            (res, _) => res,
+        })
+    }
+
+    pub async fn find_by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+        expand: bool,
+    ) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, TeamCore>(
+            r#"SELECT * FROM teams WHERE tournament_id = $1 ORDER BY name"#,
+        )
+        .bind(tournament_id)
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(Self::from);
+        Ok(match expand {
+            true => future::join_all(res.map(|t| t.expand(db))).await,
+            false => res.collect(),
+        })
+    }
+
+    pub async fn find_members(db: &Pool, team_id: u32) -> anyhow::Result<Vec<Surfer>> {
+        let query = r#"
+            SELECT s.* FROM surfers s
+            INNER JOIN team_members m
+            ON m.surfer_id = s.id
+            WHERE m.team_id = $1
+            ORDER BY s.last_name, s.first_name
+        "#;
+        let res = sqlx::query_as::<_, Surfer>(query)
+            .bind(team_id)
+            .fetch_all(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn add(db: &Pool, tournament_id: u32, team: &NewTeam) -> anyhow::Result<Self> {
+        let res = sqlx::query_as::<_, TeamCore>(
+            r#"INSERT INTO teams (tournament_id, name) VALUES ($1, $2) RETURNING *"#,
+        )
+        .bind(tournament_id)
+        .bind(&team.name)
+        .fetch_one(db)
+        .await?;
+        Ok(res.into())
+    }
+
+    pub async fn delete(db: &Pool, team_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, TeamCore>(r#"DELETE FROM teams WHERE id = $1 RETURNING *"#)
+            .bind(team_id)
+            .fetch_optional(db)
+            .await?
+            .map(Self::from);
+        Ok(res)
+    }
+}
+
+impl TeamMember {
+    pub async fn find_by_tournament_id(db: &Pool, tournament_id: u32) -> anyhow::Result<Vec<Self>> {
+        let query = r#"
+            SELECT m.* FROM team_members m
+            INNER JOIN teams t
+            ON m.team_id = t.id
+            WHERE t.tournament_id = $1
+        "#;
+        let res = sqlx::query_as::<_, TeamMember>(query)
+            .bind(tournament_id)
+            .fetch_all(db)
+            .await?;
+        Ok(res)
+    }
+
+    // a surfer can only be member of one team per tournament
+    pub async fn add(db: &Pool, team_id: u32, surfer_id: u32) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        INSERT INTO team_members (team_id, surfer_id)
+        (SELECT t.id, $2
+        FROM teams t
+        WHERE t.id = $1 AND NOT EXISTS (
+          SELECT 1 FROM team_members m
+          INNER JOIN teams o
+          ON m.team_id = o.id
+          WHERE m.surfer_id = $2 AND o.tournament_id = t.tournament_id))
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, TeamMember>(query)
+            .bind(team_id)
+            .bind(surfer_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn delete(db: &Pool, team_id: u32, surfer_id: u32) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        DELETE FROM team_members
+        WHERE team_id = $1 AND surfer_id = $2
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, TeamMember>(query)
+            .bind(team_id)
+            .bind(surfer_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::configuration::CONFIG;
+use crate::database::Pool;
+use crate::models::{
+    category::Category,
+    result::Result,
+    team::{Team, TeamMember},
+};
+use crate::score_computation::team_standings::{
+    combine_team_standings, compute_team_standings, TeamStanding,
+};
+
+use std::collections::HashMap;
+
+pub struct TeamStandings {}
+
+impl TeamStandings {
+    // standings of a team category (empty for categories without team scoring)
+    pub async fn by_category_id(db: &Pool, category_id: u32) -> anyhow::Result<Vec<TeamStanding>> {
+        let category = match Category::find_by_id(db, category_id, false).await? {
+            Some(category) => category,
+            None => return Ok(Vec::new()),
+        };
+        let team_members = Self::team_members(db, category.tournament_id as u32).await?;
+        Self::by_category(db, &category, &team_members).await
+    }
+
+    // standings summed up over all team categories of a tournament
+    pub async fn by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+    ) -> anyhow::Result<Vec<TeamStanding>> {
+        let team_members = Self::team_members(db, tournament_id).await?;
+        let mut category_standings = Vec::new();
+        for category in Category::find_by_tournament_id(db, tournament_id, false).await? {
+            if category.team_scoring.is_some() {
+                category_standings.push(Self::by_category(db, &category, &team_members).await?);
+            }
+        }
+        if category_standings.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(combine_team_standings(&category_standings))
+    }
+
+    async fn by_category(
+        db: &Pool,
+        category: &Category,
+        team_members: &HashMap<i32, Vec<i32>>,
+    ) -> anyhow::Result<Vec<TeamStanding>> {
+        let team_scoring = match category.team_scoring {
+            Some(team_scoring) => team_scoring,
+            None => return Ok(Vec::new()),
+        };
+        let results = Result::find_by_category_id(db, category.id as u32, false).await?;
+        Ok(compute_team_standings(
+            team_members,
+            &results,
+            team_scoring,
+            &CONFIG.teams.placing_points,
+            CONFIG.teams.n_best_waves,
+        ))
+    }
+
+    // surfer ids by team (including teams without members)
+    async fn team_members(db: &Pool, tournament_id: u32) -> anyhow::Result<HashMap<i32, Vec<i32>>> {
+        let mut team_members: HashMap<i32, Vec<i32>> =
+            Team::find_by_tournament_id(db, tournament_id, false)
+                .await?
+                .into_iter()
+                .map(|t| (t.id, Vec::new()))
+                .collect();
+        for member in TeamMember::find_by_tournament_id(db, tournament_id).await? {
+            team_members
+                .entry(member.team_id)
+                .or_default()
+                .push(member.surfer_id);
+        }
+        Ok(team_members)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::database::Pool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// this struct will be used to represent database record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TieDecision {
+    pub heat_id: i32,
+    pub surfer_id: i32,
+    pub rank: i32,
+    pub head_judge_id: Option<i32>,
+    pub decision_datetime: DateTime<Utc>,
+}
+
+impl TieDecision {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, TieDecision>(
+            r#"SELECT * FROM tie_decisions WHERE heat_id = $1 ORDER BY rank"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    // replace the decided order of tied surfers of a heat, the first surfer is ranked highest
+    pub async fn set(
+        db: &Pool,
+        heat_id: u32,
+        head_judge_id: u32,
+        surfer_ids: &[i32],
+    ) -> anyhow::Result<Vec<Self>> {
+        let mut tx = db.begin().await?;
+        sqlx::query(r#"DELETE FROM tie_decisions WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(&mut tx)
+            .await?;
+        for (rank, surfer_id) in surfer_ids.iter().enumerate() {
+            sqlx::query(
+                r#"
+INSERT INTO tie_decisions (heat_id, surfer_id, rank, head_judge_id, decision_datetime)
+SELECT p.heat_id, p.surfer_id, $3, $4, NOW()
+FROM participations p  -- only decisions for participating surfers
+WHERE p.heat_id = $1 AND p.surfer_id = $2;
+            "#,
+            )
+            .bind(heat_id)
+            .bind(surfer_id)
+            .bind(rank as i32)
+            .bind(head_judge_id)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::find_by_heat_id(db, heat_id).await
+    }
+
+    pub async fn delete(db: &Pool, heat_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(r#"DELETE FROM tie_decisions WHERE heat_id = $1"#)
+            .bind(heat_id)
+            .execute(db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::database::Pool;
 
 use chrono::NaiveDate;
+use chrono_tz::Tz;
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -13,6 +14,10 @@ pub struct Tournament {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub additional_info: Option<String>,
+    // start armed heats automatically
+    pub auto_start: bool,
+    // IANA time zone name (e.g. "Europe/Lisbon")
+    pub time_zone: String,
 }
 
 impl Tournament {
@@ -31,4 +36,42 @@ impl Tournament {
                 .await?;
         Ok(tournament)
     }
+
+    pub async fn find_auto_start(db: &Pool) -> anyhow::Result<Vec<Self>> {
+        let tournaments =
+            sqlx::query_as::<_, Tournament>(r#"SELECT * FROM tournaments WHERE auto_start"#)
+                .fetch_all(db)
+                .await?;
+        Ok(tournaments)
+    }
+
+    pub async fn set_time_zone(
+        db: &Pool,
+        tournament_id: u32,
+        time_zone: &Tz,
+    ) -> anyhow::Result<Option<Self>> {
+        let tournament = sqlx::query_as::<_, Tournament>(
+            r#"UPDATE tournaments SET time_zone = $2 WHERE id = $1 RETURNING *"#,
+        )
+        .bind(tournament_id)
+        .bind(time_zone.name())
+        .fetch_optional(db)
+        .await?;
+        Ok(tournament)
+    }
+
+    pub async fn set_auto_start(
+        db: &Pool,
+        tournament_id: u32,
+        auto_start: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let tournament = sqlx::query_as::<_, Tournament>(
+            r#"UPDATE tournaments SET auto_start = $2 WHERE id = $1 RETURNING *"#,
+        )
+        .bind(tournament_id)
+        .bind(auto_start)
+        .fetch_optional(db)
+        .await?;
+        Ok(tournament)
+    }
 }
@@ -0,0 +1,99 @@
+use crate::database::Pool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TournamentHold {
+    pub tournament_id: i32,
+    pub reason: String,
+    // when the next decision about continuing is expected
+    pub next_call_datetime: Option<DateTime<Utc>>,
+    pub hold_datetime: DateTime<Utc>,
+    pub held_by: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTournamentHold {
+    pub reason: String,
+    #[serde(default)]
+    pub next_call_datetime: Option<DateTime<Utc>>,
+}
+
+impl TournamentHold {
+    pub async fn find_by_tournament_id(
+        db: &Pool,
+        tournament_id: u32,
+    ) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, TournamentHold>(
+            r#"SELECT * FROM tournament_holds WHERE tournament_id = $1"#,
+        )
+        .bind(tournament_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(res)
+    }
+
+    // hold of the tournament the heat belongs to
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Option<Self>> {
+        let res = sqlx::query_as::<_, TournamentHold>(
+            r#"
+SELECT th.*
+FROM tournament_holds th
+INNER JOIN categories c
+ON th.tournament_id = c.tournament_id
+  INNER JOIN heats h
+  ON h.category_id = c.id
+WHERE h.id = $1
+        "#,
+        )
+        .bind(heat_id)
+        .fetch_optional(db)
+        .await?;
+        Ok(res)
+    }
+
+    // a new hold replaces the reason and next call of an existing one
+    pub async fn hold(
+        db: &Pool,
+        tournament_id: u32,
+        hold: &NewTournamentHold,
+        user_id: u32,
+    ) -> anyhow::Result<Self> {
+        let mut tx = db.begin().await?;
+        // wait for heats being started right now, they are running (and get paused) afterwards
+        sqlx::query(r#"SELECT id FROM tournaments WHERE id = $1 FOR UPDATE"#)
+            .bind(tournament_id)
+            .execute(&mut tx)
+            .await?;
+        let res = sqlx::query_as::<_, TournamentHold>(
+            r#"
+INSERT INTO tournament_holds (tournament_id, reason, next_call_datetime, hold_datetime, held_by)
+VALUES ($1, $2, $3, NOW(), $4)
+ON CONFLICT (tournament_id) DO UPDATE
+SET
+  reason = EXCLUDED.reason,
+  next_call_datetime = EXCLUDED.next_call_datetime
+RETURNING *;
+        "#,
+        )
+        .bind(tournament_id)
+        .bind(&hold.reason)
+        .bind(hold.next_call_datetime)
+        .bind(user_id)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(res)
+    }
+
+    pub async fn release(db: &Pool, tournament_id: u32) -> anyhow::Result<bool> {
+        let res = sqlx::query(r#"DELETE FROM tournament_holds WHERE tournament_id = $1"#)
+            .bind(tournament_id)
+            .execute(db)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+}
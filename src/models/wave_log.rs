@@ -0,0 +1,103 @@
+use crate::database::Pool;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor};
+
+// this struct will be used to represent database record
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LoggedWave {
+    pub heat_id: i32,
+    pub surfer_id: i32,
+    pub wave: i32,
+    pub logged_datetime: DateTime<Utc>,
+    pub spotter_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewLoggedWave {
+    pub surfer_id: i32,
+}
+
+impl LoggedWave {
+    pub async fn find_by_heat_id(db: &Pool, heat_id: u32) -> anyhow::Result<Vec<Self>> {
+        let res = sqlx::query_as::<_, LoggedWave>(
+            r#"SELECT * FROM wave_log WHERE heat_id = $1 ORDER BY logged_datetime"#,
+        )
+        .bind(heat_id)
+        .fetch_all(db)
+        .await?;
+        Ok(res)
+    }
+
+    // heats without any logged waves accept every wave number
+    pub async fn is_valid_wave<'e, E: PgExecutor<'e>>(
+        db: E,
+        heat_id: u32,
+        surfer_id: u32,
+        wave: i32,
+    ) -> anyhow::Result<bool> {
+        let (valid,): (bool,) = sqlx::query_as(
+            r#"
+SELECT NOT EXISTS (SELECT 1 FROM wave_log w WHERE w.heat_id = $1)
+  OR EXISTS (
+    SELECT 1 FROM wave_log w
+    WHERE w.heat_id = $1 AND w.surfer_id = $2 AND w.wave = $3
+  );
+        "#,
+        )
+        .bind(heat_id)
+        .bind(surfer_id)
+        .bind(wave)
+        .fetch_one(db)
+        .await?;
+        Ok(valid)
+    }
+
+    // log the next wave of a surfer, wave numbers are assigned per surfer starting at 0
+    pub async fn add(
+        db: &Pool,
+        heat_id: u32,
+        surfer_id: u32,
+        spotter_id: u32,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        INSERT INTO wave_log (heat_id, surfer_id, wave, logged_datetime, spotter_id)
+        (SELECT p.heat_id, p.surfer_id, (
+          SELECT COALESCE(MAX(w.wave) + 1, 0) FROM wave_log w
+          WHERE w.heat_id = $1 AND w.surfer_id = $2
+        ), NOW(), $3
+        FROM participations p  -- only waves of participating surfers
+        WHERE p.heat_id = $1 AND p.surfer_id = $2)
+        ON CONFLICT DO NOTHING  -- a concurrent tap already logged this wave
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, LoggedWave>(query)
+            .bind(heat_id)
+            .bind(surfer_id)
+            .bind(spotter_id)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn delete(
+        db: &Pool,
+        heat_id: u32,
+        surfer_id: u32,
+        wave: i32,
+    ) -> anyhow::Result<Option<Self>> {
+        let query = r#"
+        DELETE FROM wave_log
+        WHERE heat_id = $1 AND surfer_id = $2 AND wave = $3
+        RETURNING *
+        "#;
+        let res = sqlx::query_as::<_, LoggedWave>(query)
+            .bind(heat_id)
+            .bind(surfer_id)
+            .bind(wave)
+            .fetch_optional(db)
+            .await?;
+        Ok(res)
+    }
+}
@@ -23,9 +23,32 @@ pub enum Channel {
     Advancements,
     Participants,
     Scores,
+    MissingScores,
     JudgingRequests,
     JudgingAssignments,
     Heats,
+    Priority,
+    Waves,
+    Schedule,
+}
+
+impl Channel {
+    pub fn all() -> Vec<Channel> {
+        vec![
+            Channel::ActiveHeats,
+            Channel::Results,
+            Channel::Advancements,
+            Channel::Participants,
+            Channel::Scores,
+            Channel::MissingScores,
+            Channel::JudgingRequests,
+            Channel::JudgingAssignments,
+            Channel::Heats,
+            Channel::Priority,
+            Channel::Waves,
+            Channel::Schedule,
+        ]
+    }
 }
 
 // Message type sent to notifiers
@@ -1,7 +1,7 @@
 use crate::configuration::CONFIG;
 use crate::endpoints::{
-    auth, category, heat, heat_advancement, heat_state, judge, lycra_color, pages, participation,
-    result, score, surfer, tournament,
+    area, auth, calendar, category, heat, heat_advancement, heat_state, judge, lycra_color, pages,
+    participation, priority, result, schedule, score, surfer, team, time, tournament, wave_log,
 };
 
 use actix_files as fs;
@@ -37,6 +37,10 @@ pub fn public_api_routes(cfg: &mut web::ServiceConfig) {
             .route("/heats", web::get().to(heat::get_all))
             .route("/heats/{id}", web::get().to(heat::get_by_id))
             .route("/heats/{id}/results", web::get().to(result::get_by_heat_id))
+            .route(
+                "/heats/{id}/needs",
+                web::get().to(result::get_needs_by_heat_id),
+            )
             .route(
                 "/heats/{id}/participations",
                 web::get().to(participation::get_by_heat_id),
@@ -45,7 +49,33 @@ pub fn public_api_routes(cfg: &mut web::ServiceConfig) {
                 "/heats/{heat_id}/state",
                 web::get().to(heat_state::get_by_heat_id),
             )
+            .route(
+                "/heats/{heat_id}/priority",
+                web::get().to(priority::get_by_heat_id),
+            )
             .route("/active_heats", web::get().to(heat::get_active_heats))
+            .route(
+                "/tournaments/{id}/areas",
+                web::get().to(area::get_by_tournament_id),
+            )
+            .route("/areas/{id}", web::get().to(area::get_by_id))
+            .route(
+                "/tournaments/{id}/calendar.ics",
+                web::get().to(calendar::get_by_tournament_id),
+            )
+            .route(
+                "/categories/{id}/calendar.ics",
+                web::get().to(calendar::get_by_category_id),
+            )
+            .route(
+                "/surfers/{id}/calendar.ics",
+                web::get().to(calendar::get_by_surfer_id),
+            )
+            .route(
+                "/areas/{id}/active_heats",
+                web::get().to(area::get_active_heats),
+            )
+            .route("/time", web::get().to(time::get_time))
             .route("/categories", web::get().to(category::get_all))
             .route("/categories/{id}", web::get().to(category::get_by_id))
             .route(
@@ -78,6 +108,27 @@ pub fn public_api_routes(cfg: &mut web::ServiceConfig) {
                 "/tournaments/{id}/active_heats",
                 web::get().to(heat::get_active_heats_by_tournament_id),
             )
+            .route(
+                "/tournaments/{id}/teams",
+                web::get().to(team::get_by_tournament_id),
+            )
+            .route(
+                "/tournaments/{id}/team_standings",
+                web::get().to(team::get_standings_by_tournament_id),
+            )
+            .route("/teams/{id}", web::get().to(team::get_by_id))
+            .route(
+                "/tournaments/{id}/hold",
+                web::get().to(tournament::get_hold),
+            )
+            .route(
+                "/tournaments/{id}/schedule",
+                web::get().to(schedule::get_by_tournament_id),
+            )
+            .route(
+                "/categories/{id}/team_standings",
+                web::get().to(team::get_standings_by_category_id),
+            )
             .route("/results", web::get().to(result::get_all))
             .route("/results/{heat_id}", web::get().to(result::get_by_heat_id))
             .route("/participations", web::get().to(participation::get_all))
@@ -127,6 +178,55 @@ pub fn admin_api_routes(cfg: &mut web::ServiceConfig) {
                 "/heats/{heat_id}/reset_heat_time",
                 web::post().to(heat_state::reset_heat_time),
             )
+            .route(
+                "/heats/{heat_id}/adjust_heat_time",
+                web::post().to(heat_state::adjust_heat_time),
+            )
+            .route("/heats/{heat_id}/arm", web::put().to(heat_state::arm_heat))
+            .route(
+                "/heats/{heat_id}/arm",
+                web::delete().to(heat_state::disarm_heat),
+            )
+            .route(
+                "/tournaments/{tournament_id}/time_zone",
+                web::put().to(tournament::put_time_zone),
+            )
+            .route(
+                "/tournaments/{tournament_id}/auto_start",
+                web::put().to(tournament::put_auto_start),
+            )
+            .route(
+                "/tournaments/{tournament_id}/areas",
+                web::post().to(area::post),
+            )
+            .route("/areas/{area_id}", web::delete().to(area::delete))
+            .route("/heats/{heat_id}/area", web::put().to(area::put_heat_area))
+            .route("/areas/{area_id}/judges", web::get().to(area::get_judges))
+            .route(
+                "/areas/{area_id}/judges/{judge_id}",
+                web::put().to(area::add_judge),
+            )
+            .route(
+                "/areas/{area_id}/judges/{judge_id}",
+                web::delete().to(area::delete_judge),
+            )
+            .route(
+                "/tournaments/{tournament_id}/hold",
+                web::put().to(tournament::put_hold),
+            )
+            .route(
+                "/tournaments/{tournament_id}/hold",
+                web::delete().to(tournament::delete_hold),
+            )
+            .route(
+                "/tournaments/{tournament_id}/armed_heats",
+                web::get().to(tournament::get_armed_heats),
+            )
+            .route(
+                "/heats/{heat_id}/timeline",
+                web::get().to(heat_state::get_timeline_by_heat_id),
+            )
+            .route("/heats/{heat_id}/priority", web::put().to(priority::put))
             .route("/judges", web::get().to(judge::get_all))
             .route(
                 "/heats/{heat_id}/assigned_judges",
@@ -141,9 +241,58 @@ pub fn admin_api_routes(cfg: &mut web::ServiceConfig) {
                 "/heats/{heat_id}/judges/{judge_id}",
                 web::delete().to(judge::delete_assignment),
             )
+            .route(
+                "/heats/{heat_id}/judge_exclusions",
+                web::get().to(judge::get_exclusions_for_heat),
+            )
+            .route(
+                "/heats/{heat_id}/judges/{judge_id}/exclusion",
+                web::put().to(judge::add_exclusion),
+            )
+            .route(
+                "/heats/{heat_id}/judges/{judge_id}/exclusion",
+                web::delete().to(judge::delete_exclusion),
+            )
             .route(
                 "/heats/{heat_id}/preliminary_results",
                 web::get().to(result::get_preliminary_by_heat_id),
+            )
+            .route(
+                "/heats/{heat_id}/missing_scores",
+                web::get().to(score::get_missing_by_heat_id),
+            )
+            .route(
+                "/heats/{heat_id}/judge_analytics",
+                web::get().to(judge::get_analytics_by_heat_id),
+            )
+            .route(
+                "/categories/{category_id}/judge_analytics",
+                web::get().to(judge::get_analytics_by_category_id),
+            )
+            .route(
+                "/tournaments/{tournament_id}/judge_analytics",
+                web::get().to(judge::get_analytics_by_tournament_id),
+            )
+            .route(
+                "/tournaments/{tournament_id}/teams",
+                web::post().to(team::post),
+            )
+            .route("/teams/{team_id}", web::delete().to(team::delete))
+            .route(
+                "/teams/{team_id}/members/{surfer_id}",
+                web::put().to(team::add_member),
+            )
+            .route(
+                "/teams/{team_id}/members/{surfer_id}",
+                web::delete().to(team::delete_member),
+            )
+            .route(
+                "/categories/{category_id}/team_scoring",
+                web::put().to(category::put_team_scoring),
+            )
+            .route(
+                "/categories/{category_id}/tie_break",
+                web::put().to(category::put_tie_break),
             ),
     );
 }
@@ -164,12 +313,93 @@ pub fn judging_api_routes(cfg: &mut web::ServiceConfig) {
                 "/heats/{heat_id}/scores",
                 web::get().to(score::get_by_heat_id),
             )
+            .route(
+                "/heats/{heat_id}/missing_scores",
+                web::get().to(score::get_missing_by_heat_id),
+            )
+            .route(
+                "/heats/{heat_id}/tie_decision",
+                web::get().to(result::get_tie_decisions_by_heat_id),
+            )
+            .route(
+                "/heats/{heat_id}/tie_decision",
+                web::put().to(result::put_tie_decision),
+            )
+            .route(
+                "/heats/{heat_id}/tie_decision",
+                web::delete().to(result::delete_tie_decision),
+            )
+            .route(
+                "/heats/{heat_id}/score_overrides",
+                web::get().to(score::get_overrides_by_heat_id),
+            )
+            .route(
+                "/heats/{heat_id}/score_overrides",
+                web::put().to(score::put_override),
+            )
+            .route(
+                "/heats/{heat_id}/score_overrides/{surfer_id}/{wave}",
+                web::delete().to(score::delete_override),
+            )
+            .route(
+                "/heats/{heat_id}/score_lock",
+                web::get().to(score::get_lock_by_heat_id),
+            )
+            .route("/heats/{heat_id}/score_lock", web::put().to(score::lock))
+            .route(
+                "/heats/{heat_id}/score_lock",
+                web::delete().to(score::unlock),
+            )
+            .route(
+                "/heats/{heat_id}/judge_exclusions",
+                web::get().to(judge::get_exclusions_for_heat),
+            )
+            .route(
+                "/heats/{heat_id}/judges/{judge_id}/exclusion",
+                web::put().to(judge::add_exclusion),
+            )
+            .route(
+                "/heats/{heat_id}/judges/{judge_id}/exclusion",
+                web::delete().to(judge::delete_exclusion),
+            )
+            .route(
+                "/heats/{heat_id}/preliminary_results",
+                web::get().to(result::get_preliminary_by_heat_id),
+            )
             .route("/scores", web::put().to(score::put))
             .route(
                 "/scores/{heat_id}/{judge_id}/{surfer_id}/{wave}",
                 web::delete().to(score::delete),
             )
-            .route("/judging_requests", web::post().to(judge::add_request)),
+            .route(
+                "/heats/{heat_id}/score_batch",
+                web::post().to(score::post_batch),
+            )
+            .route("/judging_requests", web::post().to(judge::add_request))
+            .route(
+                "/spotting/heats/{heat_id}/priority",
+                web::get().to(priority::get_by_heat_id),
+            )
+            .route(
+                "/spotting/heats/{heat_id}/priority",
+                web::put().to(priority::put),
+            )
+            .route(
+                "/heats/{heat_id}/waves",
+                web::get().to(wave_log::get_by_heat_id),
+            )
+            .route(
+                "/spotting/heats/{heat_id}/waves",
+                web::get().to(wave_log::get_by_heat_id),
+            )
+            .route(
+                "/spotting/heats/{heat_id}/waves",
+                web::post().to(wave_log::post),
+            )
+            .route(
+                "/spotting/heats/{heat_id}/waves/{surfer_id}/{wave}",
+                web::delete().to(wave_log::delete),
+            ),
     );
 }
 
@@ -1,16 +1,49 @@
-use super::{float_eq, ResultComputation};
+use super::{Points, ResultComputation};
 
-use crate::models::result::{Result, WaveScore};
+use crate::models::result::{Result, TieBreak, WaveScore};
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 pub struct DefaultHeat {
     pub n_best_waves: usize,
+    pub tie_break: TieBreak,
+    // rank of tied surfers decided by the head judge (by surfer id)
+    pub tie_decisions: HashMap<i32, i32>,
 }
 
 impl Default for DefaultHeat {
     fn default() -> Self {
-        DefaultHeat { n_best_waves: 2 }
+        DefaultHeat {
+            n_best_waves: 2,
+            tie_break: TieBreak::RemainingWaves,
+            tie_decisions: HashMap::new(),
+        }
+    }
+}
+
+impl DefaultHeat {
+    // order of two surfers with the same total score (scores sorted from best to worst),
+    // equal if the tie break rule does not decide
+    fn compare_tied(&self, (id1, s1): (i32, &[Points]), (id2, s2): (i32, &[Points])) -> Ordering {
+        match self.tie_break {
+            TieBreak::RemainingWaves => {
+                let n_best = |s: &[Points]| s.len().min(self.n_best_waves);
+                s2[n_best(s2)..].cmp(&s1[n_best(s1)..])
+            }
+            TieBreak::HighestSingleWave => s2.first().cmp(&s1.first()),
+            TieBreak::Countback => s2.cmp(s1),
+            // decided surfers come before the ones the head judge did not rank (yet)
+            TieBreak::HeadJudgeDecision => {
+                match (self.tie_decisions.get(&id1), self.tie_decisions.get(&id2)) {
+                    (Some(r1), Some(r2)) => r1.cmp(r2),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            }
+            TieBreak::SharedPlace => Ordering::Equal,
+        }
     }
 }
 
@@ -37,78 +70,234 @@ impl ResultComputation for DefaultHeat {
             .for_each(|(_, scores)| scores.sort_by(|s1, s2| s1.wave.cmp(&s2.wave)));
 
         // determine best n waves by surfer
-        let mut ranking_scores: Vec<(i32, f64, Vec<f64>)> = scores_by_surfer
+        let mut ranking_scores: Vec<(i32, Points, Vec<Points>)> = scores_by_surfer
             .iter()
             .map(|(&surfer_id, wave_scores)| {
-                // make a copy of the vec for sorting by score
-                let mut sorted_scores = wave_scores.clone();
                 // sort waves by score
-                sorted_scores.sort_by(|s1, s2| s2.score.partial_cmp(&s1.score).unwrap());
+                let mut sorted_scores: Vec<Points> = wave_scores.iter().map(|s| s.score).collect();
+                sorted_scores.sort_by(|s1, s2| s2.cmp(s1));
 
                 // only take best n waves
-                let total_score: f64 = sorted_scores
-                    .iter()
-                    .take(self.n_best_waves)
-                    .map(|s| s.score)
-                    .sum();
-
-                // only rank scores are rounded for comparison, not total_score
-                let mut other_scores: Vec<f64> = sorted_scores
-                    .iter()
-                    .skip(self.n_best_waves)
-                    .map(|s| s.score)
-                    .collect();
-                other_scores.sort_by(|s1, s2| s2.partial_cmp(&s1).unwrap());
-                let mut rank_scores = Vec::new();
-                rank_scores.push(total_score);
-                rank_scores.extend(other_scores);
+                let total_score: Points = sorted_scores.iter().take(self.n_best_waves).sum();
 
-                (surfer_id, total_score, rank_scores)
+                (surfer_id, total_score, sorted_scores)
             })
             .collect();
 
-        // sort surfer scores lexicographically by total score and then all other scores
-        ranking_scores.sort_by(|(_, _, s1), (_, _, s2)| s2.partial_cmp(s1).unwrap());
+        // sort surfers by total score
+        ranking_scores.sort_by(|(id1, t1, _), (id2, t2, _)| t2.cmp(t1).then(id1.cmp(id2)));
+
+        // group surfers with the same total score
+        let mut tied_groups: Vec<Vec<(i32, Points, Vec<Points>)>> = Vec::new();
+        for ranking_score in ranking_scores.into_iter() {
+            match tied_groups.last_mut() {
+                Some(group) if group[0].1 == ranking_score.1 => group.push(ranking_score),
+                _ => tied_groups.push(vec![ranking_score]),
+            }
+        }
 
-        // if two surfers have exactily the same scores, they should have the same placing
+        // order tied surfers by the tie break rule, if it does not decide they share the place
         let mut results = Vec::new();
-        let mut place: i32 = 0;
-        let mut prev_place = 0;
-        let mut prev_rank_scores: Option<&Vec<f64>> = None;
-        for (idx, (surfer_id, total_score, rank_scores)) in ranking_scores.iter().enumerate() {
-            if let Some(prev) = prev_rank_scores {
-                if prev
-                    .iter()
-                    .zip(rank_scores.iter())
-                    .all(|(s1, s2)| float_eq(*s1, *s2))
-                {
-                    place = prev_place;
+        let mut group_place: i32 = 0;
+        for mut group in tied_groups.into_iter() {
+            group.sort_by(|(id1, _, s1), (id2, _, s2)| self.compare_tied((*id1, s1), (*id2, s2)));
+
+            for (surfer_id, total_score, sorted_scores) in group.iter() {
+                let same_place = |(id, _, s): &&(i32, Points, Vec<Points>)| {
+                    self.compare_tied((*id, s), (*surfer_id, sorted_scores)) == Ordering::Equal
+                };
+                let place = group_place + group.iter().position(|r| same_place(&r)).unwrap() as i32;
+                let shared = group.iter().filter(same_place).count() > 1;
+
+                // a shared place under the head judge decision rule is waiting for a decision
+                let tie_break = if group.len() == 1 {
+                    None
+                } else if !shared {
+                    Some(self.tie_break)
+                } else if self.tie_break == TieBreak::HeadJudgeDecision {
+                    Some(TieBreak::HeadJudgeDecision)
                 } else {
-                    prev_place = idx as i32;
-                    place = idx as i32;
-                }
+                    Some(TieBreak::SharedPlace)
+                };
+
+                let wave_scores: Vec<WaveScore> = scores_by_surfer
+                    .get(surfer_id)
+                    .unwrap()
+                    .iter()
+                    .map(|&s| s.clone())
+                    .collect();
+
+                results.push(Result {
+                    surfer_id: *surfer_id,
+                    heat_id,
+                    place,
+                    total_score: *total_score,
+                    wave_scores,
+                    published: false,
+                    tie_break,
+                    heat: None,
+                    surfer: None,
+                });
             }
-            prev_rank_scores = Some(rank_scores);
+            group_place += group.len() as i32;
+        }
 
-            let wave_scores: Vec<WaveScore> = scores_by_surfer
-                .get(&surfer_id)
-                .unwrap()
-                .iter()
-                .map(|&s| s.clone())
-                .collect();
-
-            results.push(Result {
-                surfer_id: *surfer_id,
-                heat_id,
-                place,
-                total_score: *total_score,
-                wave_scores,
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave_score(surfer_id: i32, wave: i32, score: f64) -> (i32, i32, Option<WaveScore>) {
+        (
+            surfer_id,
+            wave,
+            Some(WaveScore {
+                surfer_id,
+                wave,
+                score: Points::from_f64(score),
                 published: false,
-                heat: None,
-                surfer: None,
-            });
+                reduced_panel: false,
+                overridden: false,
+                kind: Default::default(),
+            }),
+        )
+    }
+
+    fn heat(tie_break: TieBreak) -> DefaultHeat {
+        DefaultHeat {
+            tie_break,
+            ..DefaultHeat::default()
         }
+    }
 
-        results
+    // (surfer_id, place, tie_break) ordered by place
+    fn placings(
+        heat: &DefaultHeat,
+        wave_scores: Vec<(i32, i32, Option<WaveScore>)>,
+    ) -> Vec<(i32, i32, Option<TieBreak>)> {
+        heat.process_wave_scores(1, &wave_scores)
+            .iter()
+            .map(|r| (r.surfer_id, r.place, r.tie_break))
+            .collect()
+    }
+
+    // same total of 14, surfer 1 has the best wave, surfer 2 the better third wave
+    fn tied_scores() -> Vec<(i32, i32, Option<WaveScore>)> {
+        vec![
+            wave_score(1, 0, 8.0),
+            wave_score(2, 0, 7.0),
+            wave_score(1, 1, 6.0),
+            wave_score(2, 1, 7.0),
+            wave_score(1, 2, 2.0),
+            wave_score(2, 2, 5.0),
+        ]
+    }
+
+    #[test]
+    fn ranks_by_total_of_best_waves() {
+        let scores = vec![
+            wave_score(1, 0, 5.0),
+            wave_score(2, 0, 6.0),
+            wave_score(1, 1, 9.0),
+            wave_score(2, 1, 7.0),
+            wave_score(1, 2, 1.0),
+        ];
+        let results = DefaultHeat::default().process_wave_scores(1, &scores);
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.surfer_id, r.place, r.total_score.to_f64(), r.tie_break))
+                .collect::<Vec<_>>(),
+            vec![(1, 0, 14.0, None), (2, 1, 13.0, None)]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_on_remaining_waves_by_default() {
+        assert_eq!(
+            placings(&DefaultHeat::default(), tied_scores()),
+            vec![
+                (2, 0, Some(TieBreak::RemainingWaves)),
+                (1, 1, Some(TieBreak::RemainingWaves))
+            ]
+        );
+    }
+
+    #[test]
+    fn shares_place_without_remaining_waves() {
+        let scores = vec![
+            wave_score(1, 0, 8.0),
+            wave_score(2, 0, 7.0),
+            wave_score(1, 1, 6.0),
+            wave_score(2, 1, 7.0),
+        ];
+        assert_eq!(
+            placings(&DefaultHeat::default(), scores),
+            vec![
+                (1, 0, Some(TieBreak::SharedPlace)),
+                (2, 0, Some(TieBreak::SharedPlace))
+            ]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_on_highest_single_wave() {
+        assert_eq!(
+            placings(&heat(TieBreak::HighestSingleWave), tied_scores()),
+            vec![
+                (1, 0, Some(TieBreak::HighestSingleWave)),
+                (2, 1, Some(TieBreak::HighestSingleWave))
+            ]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_on_countback() {
+        // same best wave, surfer 2 has the better second wave
+        let scores = vec![
+            wave_score(1, 0, 8.0),
+            wave_score(2, 0, 8.0),
+            wave_score(1, 1, 6.0),
+            wave_score(2, 1, 6.0),
+            wave_score(1, 2, 2.0),
+            wave_score(2, 2, 4.0),
+        ];
+        assert_eq!(
+            placings(&heat(TieBreak::Countback), scores),
+            vec![
+                (2, 0, Some(TieBreak::Countback)),
+                (1, 1, Some(TieBreak::Countback))
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_ties_for_head_judge_decision() {
+        assert_eq!(
+            placings(&heat(TieBreak::HeadJudgeDecision), tied_scores()),
+            vec![
+                (1, 0, Some(TieBreak::HeadJudgeDecision)),
+                (2, 0, Some(TieBreak::HeadJudgeDecision))
+            ]
+        );
+    }
+
+    #[test]
+    fn orders_ties_by_head_judge_decision() {
+        let heat = DefaultHeat {
+            tie_break: TieBreak::HeadJudgeDecision,
+            tie_decisions: vec![(2, 0), (1, 1)].into_iter().collect(),
+            ..DefaultHeat::default()
+        };
+        assert_eq!(
+            placings(&heat, tied_scores()),
+            vec![
+                (2, 0, Some(TieBreak::HeadJudgeDecision)),
+                (1, 1, Some(TieBreak::HeadJudgeDecision))
+            ]
+        );
     }
 }
@@ -0,0 +1,269 @@
+use super::{
+    compute_individual_score, drops_scores, excluded_from_waves, ranked_wave_scores, wave_panel,
+    JudgingPanel, Points, ResultComputation, DROP_SCORES,
+};
+
+use crate::models::{
+    result::{Result, WaveScore},
+    score::Score,
+};
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+
+// everything needed to compare the judges of one heat
+pub struct HeatJudging<'a> {
+    pub heat_id: i32,
+    pub panel: JudgingPanel<'a>,
+    pub scores: &'a [Score],
+    pub placings: &'a [Result],
+    pub score_processor: &'a dyn ResultComputation,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DroppedScore {
+    High,
+    Low,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaveDeviation {
+    pub heat_id: i32,
+    pub surfer_id: i32,
+    pub wave: i32,
+    pub score: Points,
+    pub panel_mean: f64,
+    pub deviation: f64,
+    pub dropped: Option<DroppedScore>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JudgeStatistics {
+    pub judge_id: i32,
+    pub n_waves: usize,
+    // mean signed deviation from the panel mean
+    pub bias: f64,
+    pub mean_absolute_deviation: f64,
+    pub n_dropped_high: usize,
+    pub n_dropped_low: usize,
+    // mean over all heats of the correlation between the judge's and the final placing
+    pub rank_correlation: Option<f64>,
+    pub wave_deviations: Vec<WaveDeviation>,
+}
+
+pub fn compute_judge_statistics(heats: &[HeatJudging]) -> Vec<JudgeStatistics> {
+    let mut deviations_by_judge = HashMap::<i32, Vec<WaveDeviation>>::new();
+    let mut correlations_by_judge = HashMap::<i32, Vec<f64>>::new();
+
+    for heat in heats {
+        let judge_set: HashSet<i32> = HashSet::from_iter(heat.panel.judges.iter().map(|j| j.id));
+        let excluded_from = excluded_from_waves(heat.panel.exclusions);
+
+        // divide scores by wave id and surfer (and filter relevant judges)
+        let scores_grouped = heat
+            .scores
+            .iter()
+            .filter(|s| judge_set.contains(&s.judge_id))
+            .fold(HashMap::<(i32, i32), Vec<&Score>>::new(), |mut acc, s| {
+                acc.entry((s.surfer_id, s.wave)).or_default().push(s);
+                acc
+            });
+
+        for ((surfer_id, wave), wave_scores) in scores_grouped.iter() {
+            // only waves that count for the result are compared
+            let wave_score = compute_individual_score(
+                *surfer_id,
+                *wave,
+                &judge_set,
+                &excluded_from,
+                heat.panel.quorum,
+                wave_scores,
+            );
+            if wave_score.is_none() {
+                continue;
+            }
+
+            let panel = wave_panel(*wave, &judge_set, &excluded_from);
+            let panel_scores: Vec<&Score> = wave_scores
+                .iter()
+                .filter(|s| panel.contains(&s.judge_id))
+                .copied()
+                .collect();
+            // ranked like in compute_individual_score (with missed scores filled in),
+            // so the dropped scores are the ones dropped for the result
+            let ranked_scores = match ranked_wave_scores(&panel_scores) {
+                Some(ranked_scores) => ranked_scores,
+                None => continue,
+            };
+            let given: Vec<f64> = panel_scores
+                .iter()
+                .filter(|s| !s.missed)
+                .map(|s| s.score.to_f64())
+                .collect();
+            let panel_mean = given.iter().sum::<f64>() / given.len() as f64;
+            let drops = drops_scores(ranked_scores.len());
+
+            for (idx, (s, _)) in ranked_scores.iter().enumerate() {
+                // missed scores have no deviation
+                if s.missed {
+                    continue;
+                }
+                let dropped = if !drops {
+                    None
+                } else if idx < DROP_SCORES {
+                    Some(DroppedScore::Low)
+                } else if idx >= ranked_scores.len() - DROP_SCORES {
+                    Some(DroppedScore::High)
+                } else {
+                    None
+                };
+                deviations_by_judge
+                    .entry(s.judge_id)
+                    .or_default()
+                    .push(WaveDeviation {
+                        heat_id: heat.heat_id,
+                        surfer_id: *surfer_id,
+                        wave: *wave,
+                        score: s.score,
+                        panel_mean,
+                        deviation: s.score.to_f64() - panel_mean,
+                        dropped,
+                    });
+            }
+        }
+
+        // compare the placing resulting from each judge's own scores with the final placing
+        let final_places: HashMap<i32, i32> =
+            HashMap::from_iter(heat.placings.iter().map(|r| (r.surfer_id, r.place)));
+        for judge_id in judge_set.iter() {
+            let judge_wave_scores: Vec<(i32, i32, Option<WaveScore>)> = heat
+                .scores
+                .iter()
+                .filter(|s| s.judge_id == *judge_id && !s.missed)
+                .map(|s| {
+                    (
+                        s.surfer_id,
+                        s.wave,
+                        Some(WaveScore {
+                            surfer_id: s.surfer_id,
+                            wave: s.wave,
+                            score: s.score,
+                            published: false,
+                            reduced_panel: false,
+                            overridden: false,
+                            kind: s.kind,
+                        }),
+                    )
+                })
+                .collect();
+            let judge_results = heat
+                .score_processor
+                .process_wave_scores(heat.heat_id, &judge_wave_scores);
+            let places: Vec<(f64, f64)> = judge_results
+                .iter()
+                .filter_map(|r| {
+                    final_places
+                        .get(&r.surfer_id)
+                        .map(|place| (r.place as f64, *place as f64))
+                })
+                .collect();
+            if let Some(correlation) = rank_correlation(&places) {
+                correlations_by_judge
+                    .entry(*judge_id)
+                    .or_default()
+                    .push(correlation);
+            }
+        }
+    }
+
+    let judge_ids: HashSet<i32> = deviations_by_judge
+        .keys()
+        .chain(correlations_by_judge.keys())
+        .copied()
+        .collect();
+    let mut statistics: Vec<JudgeStatistics> = judge_ids
+        .into_iter()
+        .map(|judge_id| {
+            let wave_deviations = deviations_by_judge.remove(&judge_id).unwrap_or_default();
+            let n_waves = wave_deviations.len();
+            let mean = |values: Vec<f64>| {
+                if values.is_empty() {
+                    0.0
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                }
+            };
+            let bias = mean(wave_deviations.iter().map(|d| d.deviation).collect());
+            let mean_absolute_deviation =
+                mean(wave_deviations.iter().map(|d| d.deviation.abs()).collect());
+            let n_dropped = |dropped: DroppedScore| {
+                wave_deviations
+                    .iter()
+                    .filter(|d| d.dropped == Some(dropped))
+                    .count()
+            };
+            let rank_correlation = correlations_by_judge.remove(&judge_id).map(mean);
+
+            JudgeStatistics {
+                judge_id,
+                n_waves,
+                bias,
+                mean_absolute_deviation,
+                n_dropped_high: n_dropped(DroppedScore::High),
+                n_dropped_low: n_dropped(DroppedScore::Low),
+                rank_correlation,
+                wave_deviations,
+            }
+        })
+        .collect();
+    statistics.sort_by_key(|s| s.judge_id);
+    statistics
+}
+
+// spearman correlation of the given pairs of places
+// (places of surfers missing in one of the placings leave gaps, so they are ranked again)
+fn rank_correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    let xs = ranks(&pairs.iter().map(|(x, _)| *x).collect::<Vec<f64>>());
+    let ys = ranks(&pairs.iter().map(|(_, y)| *y).collect::<Vec<f64>>());
+    let ranked: Vec<(f64, f64)> = xs.into_iter().zip(ys).collect();
+    correlation(&ranked)
+}
+
+// ranks starting at 1, tied values get the mean of the ranks they span
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].partial_cmp(&values[*b]).unwrap());
+    let mut ranks = vec![0.0; values.len()];
+    let mut first = 0;
+    while first < order.len() {
+        let mut last = first;
+        while last + 1 < order.len() && values[order[last + 1]] == values[order[first]] {
+            last += 1;
+        }
+        let rank = (first + last) as f64 / 2.0 + 1.0;
+        for idx in order[first..=last].iter() {
+            ranks[*idx] = rank;
+        }
+        first = last + 1;
+    }
+    ranks
+}
+
+// pearson correlation of the given pairs
+fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let cov: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let var_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let var_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
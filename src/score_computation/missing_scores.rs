@@ -0,0 +1,61 @@
+use super::{excluded_from_waves, wave_panel, JudgingPanel};
+
+use crate::models::{score::Score, wave_log::LoggedWave};
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+
+#[derive(Debug, Serialize)]
+pub struct MissingScore {
+    pub surfer_id: i32,
+    pub wave: i32,
+    pub judge_ids: Vec<i32>,
+}
+
+// started waves (logged by a spotter or scored by at least one judge) that are still missing
+// scores by judges of the panel
+pub fn find_missing_scores(
+    panel: &JudgingPanel,
+    scores: &[Score],
+    logged_waves: &[LoggedWave],
+) -> Vec<MissingScore> {
+    let judge_set: HashSet<i32> = HashSet::from_iter(panel.judges.iter().map(|j| j.id));
+    let excluded_from = excluded_from_waves(panel.exclusions);
+
+    // collect judges that scored a wave by surfer and wave, starting with all logged waves
+    let logged: HashMap<(i32, i32), HashSet<i32>> = logged_waves
+        .iter()
+        .map(|w| ((w.surfer_id, w.wave), HashSet::new()))
+        .collect();
+    let scored_by = scores
+        .iter()
+        .filter(|s| judge_set.contains(&s.judge_id))
+        .fold(logged, |mut acc, s| {
+            acc.entry((s.surfer_id, s.wave))
+                .or_default()
+                .insert(s.judge_id);
+            acc
+        });
+
+    let mut missing_scores: Vec<MissingScore> = scored_by
+        .iter()
+        .filter_map(|((surfer_id, wave), judge_ids)| {
+            let mut missing: Vec<i32> = wave_panel(*wave, &judge_set, &excluded_from)
+                .difference(judge_ids)
+                .copied()
+                .collect();
+            if missing.is_empty() {
+                return None;
+            }
+            missing.sort();
+            Some(MissingScore {
+                surfer_id: *surfer_id,
+                wave: *wave,
+                judge_ids: missing,
+            })
+        })
+        .collect();
+    missing_scores.sort_by_key(|m| (m.surfer_id, m.wave));
+    missing_scores
+}
@@ -1,7 +1,12 @@
+use crate::configuration::CONFIG;
 use crate::logging::LOG;
 use crate::models::{
-    result::{Result, WaveScore},
-    score::Score,
+    heat::HeatType,
+    judge::JudgeExclusion,
+    result::{Result, TieBreak, WaveScore},
+    score::{Score, ScoreKind},
+    score_override::ScoreOverride,
+    tie_decision::TieDecision,
     user::User,
 };
 
@@ -10,7 +15,19 @@ use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 
 pub mod default_heat;
+pub mod judge_analytics;
+pub mod missing_scores;
+pub mod needs;
+pub mod points;
 pub mod rsl_heat;
+pub mod team_standings;
+pub mod weighted_criteria_heat;
+
+use default_heat::DefaultHeat;
+use rsl_heat::RSLHeat;
+use weighted_criteria_heat::WeightedCriteriaHeat;
+
+pub use points::Points;
 
 pub trait ResultComputation {
     fn process_wave_scores(
@@ -20,19 +37,55 @@ pub trait ResultComputation {
     ) -> Vec<Result>;
 }
 
-const EPSILON: f64 = 1e-5;
+// result computation used for a heat type
+// (standard heats break ties by the given rule, or the configured one)
+pub fn result_computation(
+    heat_type: &HeatType,
+    tie_break: Option<TieBreak>,
+    tie_decisions: &[TieDecision],
+) -> Box<dyn ResultComputation> {
+    match heat_type {
+        HeatType::Standard => Box::new(DefaultHeat {
+            tie_break: tie_break.unwrap_or(CONFIG.judging.tie_break),
+            tie_decisions: tie_decisions
+                .iter()
+                .map(|d| (d.surfer_id, d.rank))
+                .collect(),
+            ..DefaultHeat::default()
+        }),
+        HeatType::Call => Box::new(RSLHeat {
+            points_per_wave_won: CONFIG.judging.call.points_per_wave_won,
+            max_waves: CONFIG.judging.call.max_waves,
+        }),
+        HeatType::Criteria => Box::new(WeightedCriteriaHeat {
+            criteria: CONFIG.judging.criteria.clone(),
+        }),
+    }
+}
+
+// judges scoring a heat and the rules for when their scores count
+pub struct JudgingPanel<'a> {
+    pub judges: &'a [User],
+    pub exclusions: &'a [JudgeExclusion],
+    pub quorum: Option<usize>,
+}
+
 const MIN_JUDGES_FOR_DROP: usize = 4;
 const DROP_SCORES: usize = 1;
 
 pub fn compute_results(
     heat_id: i32,
-    judges: &[User],
+    panel: &JudgingPanel,
     scores: &[Score],
+    overrides: &[ScoreOverride],
     results: &[Result],
-    score_processor: &impl ResultComputation,
+    score_processor: &dyn ResultComputation,
 ) -> Vec<Result> {
     // set of judge_ids for filtering
-    let judge_set: HashSet<i32> = HashSet::from_iter(judges.iter().map(|j| j.id));
+    let judge_set: HashSet<i32> = HashSet::from_iter(panel.judges.iter().map(|j| j.id));
+
+    // first wave from which on a judge is excluded from the panel
+    let excluded_from = excluded_from_waves(panel.exclusions);
 
     // divide scores by wave id and surfer (and filter relevant judges)
     let scores_grouped = scores
@@ -46,17 +99,48 @@ pub fn compute_results(
         });
 
     // compute individual results per wave and surfer (use compute_individual_score)
-    let wave_scores: Vec<(i32, i32, Option<WaveScore>)> = scores_grouped
+    let mut wave_scores: HashMap<(i32, i32), Option<WaveScore>> = scores_grouped
         .iter()
         .map(|((surfer_id, wave), individual_scores)| {
             (
-                *surfer_id,
-                *wave,
-                compute_individual_score(*surfer_id, *wave, &judge_set, individual_scores),
+                (*surfer_id, *wave),
+                compute_individual_score(
+                    *surfer_id,
+                    *wave,
+                    &judge_set,
+                    &excluded_from,
+                    panel.quorum,
+                    individual_scores,
+                ),
             )
         })
         .collect();
 
+    // overrides by the head judge replace the computed wave scores
+    overrides.iter().for_each(|o| {
+        // an override keeps the kind of the scored ride
+        let kind = scores_grouped
+            .get(&(o.surfer_id, o.wave))
+            .map_or(ScoreKind::default(), |s| ride_kind(s));
+        let wave_score = match (o.voided, o.score) {
+            (false, Some(score)) => Some(WaveScore {
+                surfer_id: o.surfer_id,
+                wave: o.wave,
+                score,
+                published: false,
+                reduced_panel: false,
+                overridden: true,
+                kind,
+            }),
+            _ => None,
+        };
+        wave_scores.insert((o.surfer_id, o.wave), wave_score);
+    });
+    let wave_scores: Vec<(i32, i32, Option<WaveScore>)> = wave_scores
+        .into_iter()
+        .map(|((surfer_id, wave), wave_score)| (surfer_id, wave, wave_score))
+        .collect();
+
     let mut preliminary_results = score_processor.process_wave_scores(heat_id, &wave_scores);
 
     let grouped_results = results
@@ -77,14 +161,14 @@ pub fn compute_results(
             });
     preliminary_results.iter_mut().for_each(|pr| {
         if let Some(existing_result) = grouped_results.get(&pr.surfer_id) {
-            if float_eq(existing_result.total_score, pr.total_score) {
+            if existing_result.total_score == pr.total_score {
                 pr.published = true;
             }
         }
         pr.wave_scores.iter_mut().for_each(|ws| {
             if let Some(existing_wave_score) = grouped_pub_wave_scores.get(&(ws.surfer_id, ws.wave))
             {
-                if float_eq(existing_wave_score.score, ws.score) {
+                if existing_wave_score.score == ws.score {
                     ws.published = true;
                 }
             }
@@ -94,48 +178,102 @@ pub fn compute_results(
     preliminary_results
 }
 
-fn float_eq(val1: f64, val2: f64) -> bool {
-    (val1 - val2).abs() < EPSILON
+// kind of a ride as scored by the majority of judges
+fn ride_kind(scores: &[&Score]) -> ScoreKind {
+    let n_jumps = scores.iter().filter(|s| s.kind == ScoreKind::Jump).count();
+    if 2 * n_jumps > scores.len() {
+        ScoreKind::Jump
+    } else {
+        ScoreKind::Wave
+    }
+}
+
+fn excluded_from_waves(exclusions: &[JudgeExclusion]) -> HashMap<i32, i32> {
+    HashMap::from_iter(exclusions.iter().map(|e| (e.judge_id, e.from_wave)))
+}
+
+// judges that were not excluded from the panel before the given wave
+fn wave_panel(
+    wave: i32,
+    judge_ids: &HashSet<i32>,
+    excluded_from: &HashMap<i32, i32>,
+) -> HashSet<i32> {
+    judge_ids
+        .iter()
+        .filter(|judge_id| match excluded_from.get(judge_id) {
+            Some(from_wave) => wave < *from_wave,
+            None => true,
+        })
+        .copied()
+        .collect()
+}
+
+// scores of a wave in ascending order (ties by judge), missed scores are filled with the
+// average of the other scores (None if all judges missed the wave)
+fn ranked_wave_scores<'a>(scores: &[&'a Score]) -> Option<Vec<(&'a Score, Points)>> {
+    let given: Vec<Points> = scores
+        .iter()
+        .filter(|s| !s.missed)
+        .map(|s| s.score)
+        .collect();
+    if given.is_empty() {
+        return None;
+    }
+    let missed_substitute = given.iter().sum::<Points>().div_round(given.len());
+    let mut ranked_scores: Vec<(&Score, Points)> = scores
+        .iter()
+        .map(|s| (*s, if s.missed { missed_substitute } else { s.score }))
+        .collect();
+    ranked_scores.sort_by(|(s1, p1), (s2, p2)| p1.cmp(p2).then(s1.judge_id.cmp(&s2.judge_id)));
+    Some(ranked_scores)
+}
+
+// whether the lowest and highest of the ranked scores of a wave are dropped
+fn drops_scores(n_scores: usize) -> bool {
+    n_scores > MIN_JUDGES_FOR_DROP.max(2 * DROP_SCORES)
 }
 
 fn compute_individual_score(
     surfer_id: i32,
     wave: i32,
     judge_ids: &HashSet<i32>,
+    excluded_from: &HashMap<i32, i32>,
+    quorum: Option<usize>,
     scores: &[&Score],
 ) -> Option<WaveScore> {
-    let score_judges: HashSet<i32> = HashSet::from_iter(scores.iter().map(|s| s.judge_id));
-    if (*judge_ids != score_judges) || (judge_ids.len() != scores.len()) {
-        debug!(
-            LOG,
-            "Not all judges provided scores for surfer {}, wave {}", surfer_id, wave
-        );
-        return None;
-    }
+    let panel = wave_panel(wave, judge_ids, excluded_from);
 
-    // sort scores by score
-    let mut ranked_scores: Vec<f64> = scores
+    // only consider scores by the judges of the panel
+    let scores: Vec<&Score> = scores
         .iter()
-        .filter(|s| !s.missed)
-        .map(|s| s.score)
+        .filter(|s| panel.contains(&s.judge_id))
+        .copied()
         .collect();
 
-    if ranked_scores.len() == 0 {
+    // without a quorum, every judge of the panel needs to provide a score
+    let required = quorum.map_or(panel.len(), |q| q.min(panel.len()));
+    let score_judges: HashSet<i32> = HashSet::from_iter(scores.iter().map(|s| s.judge_id));
+    if (score_judges.len() < required) || (score_judges.len() != scores.len()) {
         debug!(
             LOG,
-            "All judges missed score for surfer {}, wave {}", surfer_id, wave
+            "Not enough judges provided scores for surfer {}, wave {}", surfer_id, wave
         );
         return None;
     }
-    // fill missed scores with average of non-missed scores
-    let n_missed = scores.len() - ranked_scores.len();
-    let missed_substitute = ranked_scores.iter().sum::<f64>() / ranked_scores.len() as f64;
-    ranked_scores.extend(vec![missed_substitute; n_missed]);
+    let reduced_panel = scores.len() < judge_ids.len();
 
-    // sort scores before removing first and last ones
-    ranked_scores.sort_by(|s1, s2| s1.partial_cmp(&s2).unwrap());
+    let ranked_scores = match ranked_wave_scores(&scores) {
+        Some(ranked_scores) => ranked_scores,
+        None => {
+            debug!(
+                LOG,
+                "All judges missed score for surfer {}, wave {}", surfer_id, wave
+            );
+            return None;
+        }
+    };
 
-    let score = if (scores.len() > MIN_JUDGES_FOR_DROP) && (ranked_scores.len() > 2 * DROP_SCORES) {
+    let score = if drops_scores(ranked_scores.len()) {
         let n = ranked_scores.len() - 2 * DROP_SCORES;
 
         // remove best and worst score
@@ -144,16 +282,23 @@ fn compute_individual_score(
             .iter()
             .skip(DROP_SCORES)
             .take(n)
-            .map(|s| s)
-            .sum::<f64>()
-            / n as f64
+            .map(|(_, score)| *score)
+            .sum::<Points>()
+            .div_round(n)
     } else {
-        ranked_scores.iter().sum::<f64>() / scores.len() as f64
+        ranked_scores
+            .iter()
+            .map(|(_, score)| *score)
+            .sum::<Points>()
+            .div_round(scores.len())
     };
     Some(WaveScore {
         surfer_id,
         wave,
         score,
         published: false,
+        reduced_panel,
+        overridden: false,
+        kind: ride_kind(&scores),
     })
 }
@@ -0,0 +1,126 @@
+use super::{Points, ResultComputation};
+
+use crate::models::{
+    result::{Result, WaveScore},
+    score::ScoreKind,
+};
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+pub struct PlaceNeed {
+    pub place: i32,
+    // None if the place can not be reached with a single wave
+    pub score: Option<Points>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SurferNeeds {
+    pub surfer_id: i32,
+    pub place: i32,
+    pub needs: Vec<PlaceNeed>,
+}
+
+// score each surfer needs on their next wave to move into each better place
+pub fn compute_needs(
+    heat_id: i32,
+    surfer_ids: &[i32],
+    results: &[Result],
+    score_processor: &dyn ResultComputation,
+) -> Vec<SurferNeeds> {
+    let wave_scores: Vec<(i32, i32, Option<WaveScore>)> = results
+        .iter()
+        .flat_map(|r| r.wave_scores.iter())
+        .map(|ws| (ws.surfer_id, ws.wave, Some(ws.clone())))
+        .collect();
+
+    // the next wave of a surfer is not ridden by anyone else
+    let next_wave = wave_scores
+        .iter()
+        .map(|(_, wave, _)| *wave + 1)
+        .max()
+        .unwrap_or(0);
+
+    let places = placing(heat_id, surfer_ids, &wave_scores, score_processor);
+
+    let mut needs: Vec<SurferNeeds> = surfer_ids
+        .iter()
+        .map(|&surfer_id| {
+            let place = *places.get(&surfer_id).unwrap();
+
+            // place of the surfer after riding a wave with the given score
+            let place_with = |score: Points| {
+                let mut hypothetical = wave_scores.clone();
+                hypothetical.push((
+                    surfer_id,
+                    next_wave,
+                    Some(WaveScore {
+                        surfer_id,
+                        wave: next_wave,
+                        score,
+                        published: false,
+                        reduced_panel: false,
+                        overridden: false,
+                        kind: ScoreKind::Wave,
+                    }),
+                ));
+                *placing(heat_id, surfer_ids, &hypothetical, score_processor)
+                    .get(&surfer_id)
+                    .unwrap()
+            };
+
+            let needs = (0..place)
+                .map(|target| {
+                    let score = min_score_for_place(target, &place_with);
+                    PlaceNeed {
+                        place: target,
+                        score,
+                    }
+                })
+                .collect();
+
+            SurferNeeds {
+                surfer_id,
+                place,
+                needs,
+            }
+        })
+        .collect();
+    needs.sort_by_key(|n| (n.place, n.surfer_id));
+    needs
+}
+
+// binary search for the smallest score that reaches the target place
+// scores are searched in hundredths between 0 and the highest wave score
+fn min_score_for_place(target: i32, place_with: &dyn Fn(Points) -> i32) -> Option<Points> {
+    if place_with(Points::MAX_WAVE_SCORE) > target {
+        return None;
+    }
+    let (mut lo, mut hi) = (0, Points::MAX_WAVE_SCORE.hundredths());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if place_with(Points::from_hundredths(mid)) <= target {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(Points::from_hundredths(lo))
+}
+
+// places of all surfers, surfers without any scored waves share the last place
+fn placing(
+    heat_id: i32,
+    surfer_ids: &[i32],
+    wave_scores: &Vec<(i32, i32, Option<WaveScore>)>,
+    score_processor: &dyn ResultComputation,
+) -> HashMap<i32, i32> {
+    let results = score_processor.process_wave_scores(heat_id, wave_scores);
+    let last_place = results.len() as i32;
+    let mut places: HashMap<i32, i32> = results.iter().map(|r| (r.surfer_id, r.place)).collect();
+    surfer_ids.iter().for_each(|surfer_id| {
+        places.entry(*surfer_id).or_insert(last_place);
+    });
+    places
+}
@@ -0,0 +1,156 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres},
+    Decode, Encode, Type,
+};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+
+// number of units per point (scores have two decimals)
+const SCALE: i64 = 100;
+
+// fixed-point score with two decimals, stored as hundredths to avoid floating point artefacts
+// (serialized as a rounded number, stored as a double precision in the database)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Points(i64);
+
+impl Points {
+    pub const ZERO: Points = Points(0);
+    // highest score of a single wave
+    pub const MAX_WAVE_SCORE: Points = Points(10 * SCALE);
+
+    pub const fn from_hundredths(hundredths: i64) -> Self {
+        Points(hundredths)
+    }
+
+    pub fn hundredths(self) -> i64 {
+        self.0
+    }
+
+    // rounds to the nearest hundredth
+    pub fn from_f64(value: f64) -> Self {
+        Points((value * SCALE as f64).round() as i64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    pub fn scale(self, factor: f64) -> Self {
+        Points((self.0 as f64 * factor).round() as i64)
+    }
+
+    // divide into n parts (rounded half away from zero)
+    pub fn div_round(self, n: usize) -> Self {
+        let n = n as i64;
+        let rounded = (2 * self.0.abs() + n) / (2 * n);
+        Points(self.0.signum() * rounded)
+    }
+}
+
+impl fmt::Display for Points {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{:02}",
+            sign,
+            self.0.abs() / SCALE,
+            self.0.abs() % SCALE
+        )
+    }
+}
+
+impl Add for Points {
+    type Output = Points;
+
+    fn add(self, other: Points) -> Points {
+        Points(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Points {
+    fn add_assign(&mut self, other: Points) {
+        self.0 += other.0;
+    }
+}
+
+impl Sum for Points {
+    fn sum<I: Iterator<Item = Points>>(iter: I) -> Points {
+        iter.fold(Points::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Points> for Points {
+    fn sum<I: Iterator<Item = &'a Points>>(iter: I) -> Points {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Points {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+// existing clients send numbers, strings like "7.50" are accepted as well
+impl<'de> Deserialize<'de> for Points {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PointsVisitor;
+
+        impl<'de> de::Visitor<'de> for PointsVisitor {
+            type Value = Points;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number or a decimal string")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Points, E> {
+                Ok(Points::from_f64(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Points, E> {
+                Ok(Points(value * SCALE))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Points, E> {
+                Ok(Points(value as i64 * SCALE))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Points, E> {
+                value
+                    .trim()
+                    .parse::<f64>()
+                    .map(Points::from_f64)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(PointsVisitor)
+    }
+}
+
+impl Type<Postgres> for Points {
+    fn type_info() -> PgTypeInfo {
+        <f64 as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <f64 as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Points {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Points::from_f64(<f64 as Decode<Postgres>>::decode(value)?))
+    }
+}
+
+impl Encode<'_, Postgres> for Points {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <f64 as Encode<Postgres>>::encode_by_ref(&self.to_f64(), buf)
+    }
+}
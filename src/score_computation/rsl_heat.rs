@@ -1,10 +1,27 @@
-use super::{float_eq, ResultComputation};
+use super::{Points, ResultComputation};
 
 use crate::models::result::{Result, WaveScore};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-pub struct RSLHeat {}
+// number of best single waves used to break ties in points
+const TIE_BREAK_WAVES: usize = 2;
+
+// head-to-head format: the best score of each wave wins points for the surfer(s) who rode it
+pub struct RSLHeat {
+    pub points_per_wave_won: Points,
+    // only the first waves (by wave number) count if set
+    pub max_waves: Option<usize>,
+}
+
+impl Default for RSLHeat {
+    fn default() -> Self {
+        RSLHeat {
+            points_per_wave_won: Points::from_f64(1.0),
+            max_waves: None,
+        }
+    }
+}
 
 impl ResultComputation for RSLHeat {
     fn process_wave_scores(
@@ -12,70 +29,84 @@ impl ResultComputation for RSLHeat {
         heat_id: i32,
         wave_scores: &Vec<(i32, i32, Option<WaveScore>)>,
     ) -> Vec<Result> {
-        // group scores by wave
+        // group scores by wave (ordered by wave number for the cap)
         let scores_by_wave = wave_scores.iter().fold(
-            HashMap::<i32, Vec<&WaveScore>>::new(),
+            BTreeMap::<i32, Vec<&WaveScore>>::new(),
             |mut acc, (_, wave, wave_score)| {
                 if let Some(wave_score) = wave_score {
-                    acc.entry(*wave).or_insert(Vec::new()).push(wave_score);
+                    acc.entry(*wave).or_default().push(wave_score);
                 }
                 acc
             },
         );
+        let counted_waves: Vec<(&i32, &Vec<&WaveScore>)> = scores_by_wave
+            .iter()
+            .take(self.max_waves.unwrap_or(usize::MAX))
+            .collect();
 
-        // collect wave scores by surfer
-        let mut scores_by_surfer = wave_scores.iter().fold(
-            HashMap::<i32, Vec<&WaveScore>>::new(),
-            |mut acc, (surfer_id, _, wave_score)| {
-                if let Some(wave_score) = wave_score {
-                    acc.entry(*surfer_id).or_insert(Vec::new()).push(wave_score);
-                }
-                acc
-            },
-        );
+        // collect counted wave scores by surfer
+        let mut scores_by_surfer = HashMap::<i32, Vec<&WaveScore>>::new();
+        for (_, scores) in counted_waves.iter() {
+            for s in scores.iter() {
+                scores_by_surfer.entry(s.surfer_id).or_default().push(s);
+            }
+        }
 
         // sort surfer scores by wave number
         scores_by_surfer
             .iter_mut()
-            .for_each(|(_, scores)| scores.sort_by(|s1, s2| s1.wave.cmp(&s2.wave)));
+            .for_each(|(_, scores)| scores.sort_by_key(|s| s.wave));
 
-        // determine best score
-        let mut total_scores_by_surfer: HashMap<i32, f64> = HashMap::new();
-        for (_, scores) in scores_by_wave.iter() {
-            let best_score = scores
-                .iter()
-                .max_by(|s1, s2| s1.score.partial_cmp(&s2.score).unwrap());
+        // points for the best score of each wave, split between surfers riding it simultaneously
+        let mut total_scores_by_surfer: HashMap<i32, Points> = HashMap::new();
+        for (_, scores) in counted_waves.iter() {
+            let best_score = scores.iter().map(|s| s.score).max();
             if let Some(best_score) = best_score {
+                let n_winners = scores.iter().filter(|s| s.score == best_score).count();
                 scores.iter().for_each(|s| {
-                    let e = total_scores_by_surfer.entry(s.surfer_id).or_insert(0.0);
-                    if float_eq(s.score, best_score.score) {
-                        *e += 1.0;
+                    let e = total_scores_by_surfer.entry(s.surfer_id).or_default();
+                    if s.score == best_score {
+                        *e += self.points_per_wave_won.div_round(n_winners);
                     }
                 })
             }
         }
-        let mut ranking_scores: Vec<(i32, f64)> = total_scores_by_surfer.into_iter().collect();
-        ranking_scores.sort_by(|(_, s1), (_, s2)| s2.partial_cmp(&s1).unwrap());
 
-        // if two surfers have exactily the same scores, they should have the same placing
+        // rank by points, ties are broken by the highest single waves
+        let mut ranking_scores: Vec<(i32, Points, Vec<Points>)> = total_scores_by_surfer
+            .into_iter()
+            .map(|(surfer_id, total_score)| {
+                let mut best_waves: Vec<Points> = scores_by_surfer[&surfer_id]
+                    .iter()
+                    .map(|s| s.score)
+                    .collect();
+                best_waves.sort_by(|s1, s2| s2.cmp(s1));
+                best_waves.truncate(TIE_BREAK_WAVES);
+
+                let mut rank_scores = vec![total_score];
+                rank_scores.extend(best_waves);
+                (surfer_id, total_score, rank_scores)
+            })
+            .collect();
+        ranking_scores.sort_by(|(id1, _, s1), (id2, _, s2)| s2.cmp(s1).then(id1.cmp(id2)));
+
+        // if two surfers have exactly the same scores, they should have the same placing
         let mut results = Vec::new();
         let mut place: i32 = 0;
         let mut prev_place = 0;
-        let mut prev_total_score: Option<&f64> = None;
-        for (idx, (surfer_id, total_score)) in ranking_scores.iter().enumerate() {
-            if let Some(prev) = prev_total_score {
-                if float_eq(*prev, *total_score) {
+        let mut prev_rank_scores: Option<&Vec<Points>> = None;
+        for (idx, (surfer_id, total_score, rank_scores)) in ranking_scores.iter().enumerate() {
+            if let Some(prev) = prev_rank_scores {
+                if prev == rank_scores {
                     place = prev_place;
                 } else {
                     prev_place = idx as i32;
                     place = idx as i32;
                 }
             }
-            prev_total_score = Some(total_score);
+            prev_rank_scores = Some(rank_scores);
 
-            let wave_scores: Vec<WaveScore> = scores_by_surfer
-                .get(&surfer_id)
-                .unwrap()
+            let wave_scores: Vec<WaveScore> = scores_by_surfer[surfer_id]
                 .iter()
                 .map(|&s| s.clone())
                 .collect();
@@ -87,6 +118,7 @@ impl ResultComputation for RSLHeat {
                 place,
                 wave_scores,
                 published: false,
+                tie_break: None,
                 heat: None,
                 surfer: None,
             });
@@ -94,3 +126,156 @@ impl ResultComputation for RSLHeat {
         results
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave_score(surfer_id: i32, wave: i32, score: f64) -> (i32, i32, Option<WaveScore>) {
+        (
+            surfer_id,
+            wave,
+            Some(WaveScore {
+                surfer_id,
+                wave,
+                score: Points::from_f64(score),
+                published: false,
+                reduced_panel: false,
+                overridden: false,
+                kind: Default::default(),
+            }),
+        )
+    }
+
+    // (surfer_id, place, total_score) ordered by place
+    fn placings(
+        heat: &RSLHeat,
+        wave_scores: Vec<(i32, i32, Option<WaveScore>)>,
+    ) -> Vec<(i32, i32, f64)> {
+        heat.process_wave_scores(1, &wave_scores)
+            .iter()
+            .map(|r| (r.surfer_id, r.place, r.total_score.to_f64()))
+            .collect()
+    }
+
+    #[test]
+    fn counts_waves_won() {
+        let scores = vec![
+            wave_score(1, 0, 7.0),
+            wave_score(2, 0, 5.0),
+            wave_score(1, 1, 3.0),
+            wave_score(2, 1, 6.0),
+            wave_score(1, 2, 8.0),
+            wave_score(2, 2, 2.0),
+        ];
+        assert_eq!(
+            placings(&RSLHeat::default(), scores),
+            vec![(1, 0, 2.0), (2, 1, 1.0)]
+        );
+    }
+
+    #[test]
+    fn configurable_points_per_wave_won() {
+        let heat = RSLHeat {
+            points_per_wave_won: Points::from_f64(3.0),
+            max_waves: None,
+        };
+        let scores = vec![
+            wave_score(1, 0, 7.0),
+            wave_score(2, 0, 5.0),
+            wave_score(1, 1, 3.0),
+            wave_score(2, 1, 6.0),
+            wave_score(1, 2, 8.0),
+            wave_score(2, 2, 2.0),
+        ];
+        assert_eq!(placings(&heat, scores), vec![(1, 0, 6.0), (2, 1, 3.0)]);
+    }
+
+    #[test]
+    fn splits_points_of_simultaneous_best_waves() {
+        // wave 0 is a tie between surfers 1 and 2, surfer 3 wins wave 1
+        let scores = vec![
+            wave_score(1, 0, 6.0),
+            wave_score(2, 0, 6.0),
+            wave_score(3, 0, 4.0),
+            wave_score(1, 1, 2.0),
+            wave_score(2, 1, 1.0),
+            wave_score(3, 1, 5.0),
+        ];
+        assert_eq!(
+            placings(&RSLHeat::default(), scores),
+            vec![(3, 0, 1.0), (1, 1, 0.5), (2, 2, 0.5)]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_on_highest_single_wave() {
+        // one wave won each, surfer 2 has the highest single wave
+        let scores = vec![
+            wave_score(1, 0, 7.0),
+            wave_score(2, 0, 5.0),
+            wave_score(1, 1, 3.0),
+            wave_score(2, 1, 9.0),
+        ];
+        assert_eq!(
+            placings(&RSLHeat::default(), scores),
+            vec![(2, 0, 1.0), (1, 1, 1.0)]
+        );
+    }
+
+    #[test]
+    fn breaks_ties_on_second_highest_wave() {
+        // one wave won each, same best wave, surfer 1 has the better second wave
+        let scores = vec![
+            wave_score(1, 0, 8.0),
+            wave_score(2, 0, 4.0),
+            wave_score(1, 1, 6.0),
+            wave_score(2, 1, 8.0),
+        ];
+        assert_eq!(
+            placings(&RSLHeat::default(), scores),
+            vec![(1, 0, 1.0), (2, 1, 1.0)]
+        );
+    }
+
+    #[test]
+    fn shares_place_if_tie_break_fails() {
+        let scores = vec![
+            wave_score(1, 0, 8.0),
+            wave_score(2, 0, 6.0),
+            wave_score(1, 1, 6.0),
+            wave_score(2, 1, 8.0),
+        ];
+        assert_eq!(
+            placings(&RSLHeat::default(), scores),
+            vec![(1, 0, 1.0), (2, 0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn caps_number_of_waves() {
+        // surfer 2 wins the third wave, which is not counted
+        let heat = RSLHeat {
+            points_per_wave_won: Points::from_f64(1.0),
+            max_waves: Some(2),
+        };
+        let scores = vec![
+            wave_score(1, 0, 7.0),
+            wave_score(2, 0, 5.0),
+            wave_score(1, 1, 3.0),
+            wave_score(2, 1, 6.0),
+            wave_score(1, 2, 1.0),
+            wave_score(2, 2, 9.5),
+        ];
+        let results = heat.process_wave_scores(1, &scores);
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.surfer_id, r.place, r.total_score.to_f64()))
+                .collect::<Vec<_>>(),
+            vec![(1, 0, 1.0), (2, 1, 1.0)]
+        );
+        // waves after the cap are not listed
+        assert!(results.iter().all(|r| r.wave_scores.len() == 2));
+    }
+}
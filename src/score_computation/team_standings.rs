@@ -0,0 +1,129 @@
+use super::Points;
+
+use crate::models::{result::Result, team::TeamScoring};
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MemberScore {
+    pub surfer_id: i32,
+    pub score: Points,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TeamStanding {
+    pub team_id: i32,
+    pub place: i32,
+    pub total_score: Points,
+    pub member_scores: Vec<MemberScore>,
+}
+
+// aggregate the heat results of the team members of one category
+pub fn compute_team_standings(
+    team_members: &HashMap<i32, Vec<i32>>,
+    results: &[Result],
+    team_scoring: TeamScoring,
+    placing_points: &[Points],
+    n_best_waves: usize,
+) -> Vec<TeamStanding> {
+    let results_by_surfer =
+        results
+            .iter()
+            .fold(HashMap::<i32, Vec<&Result>>::new(), |mut acc, r| {
+                acc.entry(r.surfer_id).or_default().push(r);
+                acc
+            });
+
+    let member_score = |surfer_id: i32| {
+        let surfer_results = results_by_surfer
+            .get(&surfer_id)
+            .map_or(&[][..], |r| &r[..]);
+        match team_scoring {
+            TeamScoring::PlacingPoints => surfer_results
+                .iter()
+                .filter_map(|r| placing_points.get(r.place as usize))
+                .sum(),
+            TeamScoring::BestWaves => {
+                let mut scores: Vec<Points> = surfer_results
+                    .iter()
+                    .flat_map(|r| r.wave_scores.iter().map(|ws| ws.score))
+                    .collect();
+                scores.sort_by(|s1, s2| s2.cmp(s1));
+                scores.iter().take(n_best_waves).sum()
+            }
+        }
+    };
+
+    let standings = team_members
+        .iter()
+        .map(|(team_id, surfer_ids)| {
+            let member_scores: Vec<MemberScore> = surfer_ids
+                .iter()
+                .map(|surfer_id| MemberScore {
+                    surfer_id: *surfer_id,
+                    score: member_score(*surfer_id),
+                })
+                .collect();
+            TeamStanding {
+                team_id: *team_id,
+                place: 0,
+                total_score: member_scores.iter().map(|m| m.score).sum(),
+                member_scores,
+            }
+        })
+        .collect();
+    rank_standings(standings)
+}
+
+// sum up the team standings of several categories
+pub fn combine_team_standings(category_standings: &[Vec<TeamStanding>]) -> Vec<TeamStanding> {
+    let mut combined = HashMap::<i32, (Points, HashMap<i32, Points>)>::new();
+    for standing in category_standings.iter().flatten() {
+        let (total_score, member_scores) = combined.entry(standing.team_id).or_default();
+        *total_score += standing.total_score;
+        for m in standing.member_scores.iter() {
+            *member_scores.entry(m.surfer_id).or_default() += m.score;
+        }
+    }
+
+    let standings = combined
+        .into_iter()
+        .map(|(team_id, (total_score, member_scores))| TeamStanding {
+            team_id,
+            place: 0,
+            total_score,
+            member_scores: member_scores
+                .into_iter()
+                .map(|(surfer_id, score)| MemberScore { surfer_id, score })
+                .collect(),
+        })
+        .collect();
+    rank_standings(standings)
+}
+
+// order by total score, teams with the same total share a place
+fn rank_standings(mut standings: Vec<TeamStanding>) -> Vec<TeamStanding> {
+    standings.iter_mut().for_each(|s| {
+        s.member_scores.sort_by(|m1, m2| {
+            m2.score
+                .cmp(&m1.score)
+                .then(m1.surfer_id.cmp(&m2.surfer_id))
+        })
+    });
+    standings.sort_by(|s1, s2| {
+        s2.total_score
+            .cmp(&s1.total_score)
+            .then(s1.team_id.cmp(&s2.team_id))
+    });
+
+    let mut prev: Option<(i32, Points)> = None;
+    for (idx, standing) in standings.iter_mut().enumerate() {
+        standing.place = match prev {
+            Some((place, total_score)) if total_score == standing.total_score => place,
+            _ => idx as i32,
+        };
+        prev = Some((standing.place, standing.total_score));
+    }
+    standings
+}
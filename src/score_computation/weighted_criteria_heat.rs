@@ -0,0 +1,101 @@
+use super::{Points, ResultComputation};
+
+use crate::configuration::Criterion;
+use crate::models::result::{Result, WaveScore};
+
+use std::collections::HashMap;
+
+// combines the best n scores of each kind (e.g. 2 waves and 1 jump) with a weight per kind
+pub struct WeightedCriteriaHeat {
+    pub criteria: Vec<Criterion>,
+}
+
+impl ResultComputation for WeightedCriteriaHeat {
+    fn process_wave_scores(
+        &self,
+        heat_id: i32,
+        wave_scores: &Vec<(i32, i32, Option<WaveScore>)>,
+    ) -> Vec<Result> {
+        // collect wave scores by surfer
+        let mut scores_by_surfer = wave_scores.iter().fold(
+            HashMap::<i32, Vec<&WaveScore>>::new(),
+            |mut acc, (surfer_id, _, wave_score)| {
+                if let Some(wave_score) = wave_score {
+                    acc.entry(*surfer_id).or_default().push(wave_score);
+                }
+                acc
+            },
+        );
+
+        // sort surfer scores by wave number
+        scores_by_surfer
+            .iter_mut()
+            .for_each(|(_, scores)| scores.sort_by_key(|s| s.wave));
+
+        // sum up the weighted best n scores of each kind
+        let mut ranking_scores: Vec<(i32, Points, Vec<Points>)> = scores_by_surfer
+            .iter()
+            .map(|(&surfer_id, wave_scores)| {
+                let mut total_score = Points::ZERO;
+                let mut other_scores = Vec::new();
+                for criterion in self.criteria.iter() {
+                    let mut sorted_scores: Vec<Points> = wave_scores
+                        .iter()
+                        .filter(|s| s.kind == criterion.kind)
+                        .map(|s| s.score.scale(criterion.weight))
+                        .collect();
+                    sorted_scores.sort_by(|s1, s2| s2.cmp(s1));
+
+                    total_score += sorted_scores.iter().take(criterion.n_best).sum::<Points>();
+                    other_scores.extend(sorted_scores.into_iter().skip(criterion.n_best));
+                }
+
+                // ties are broken by the remaining weighted scores
+                other_scores.sort_by(|s1, s2| s2.cmp(s1));
+                let mut rank_scores = vec![total_score];
+                rank_scores.extend(other_scores);
+
+                (surfer_id, total_score, rank_scores)
+            })
+            .collect();
+
+        // sort surfer scores lexicographically by total score and then all other scores
+        ranking_scores.sort_by(|(_, _, s1), (_, _, s2)| s2.cmp(s1));
+
+        // if two surfers have exactly the same scores, they should have the same placing
+        let mut results = Vec::new();
+        let mut place: i32 = 0;
+        let mut prev_place = 0;
+        let mut prev_rank_scores: Option<&Vec<Points>> = None;
+        for (idx, (surfer_id, total_score, rank_scores)) in ranking_scores.iter().enumerate() {
+            if let Some(prev) = prev_rank_scores {
+                if prev == rank_scores {
+                    place = prev_place;
+                } else {
+                    prev_place = idx as i32;
+                    place = idx as i32;
+                }
+            }
+            prev_rank_scores = Some(rank_scores);
+
+            let wave_scores: Vec<WaveScore> = scores_by_surfer[surfer_id]
+                .iter()
+                .map(|&s| s.clone())
+                .collect();
+
+            results.push(Result {
+                surfer_id: *surfer_id,
+                heat_id,
+                place,
+                total_score: *total_score,
+                wave_scores,
+                published: false,
+                tie_break: None,
+                heat: None,
+                surfer: None,
+            });
+        }
+
+        results
+    }
+}
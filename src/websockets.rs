@@ -1,3 +1,4 @@
+use crate::endpoints::time::TimeSync;
 use crate::logging::LOG;
 use crate::notifier::{Channel, Notifier};
 
@@ -12,6 +13,7 @@ use std::{
 use actix::prelude::*;
 use actix_web::{error, web, Error, HttpRequest, HttpResponse, Result};
 use actix_web_actors::ws;
+use chrono::Utc;
 
 /// Entry point for the websocket route
 pub async fn ws_route(
@@ -62,8 +64,11 @@ impl fmt::Display for ClientID {
 // message to be received from websocket connections
 #[derive(Serialize, Deserialize, Debug)]
 pub struct WSClientRequestMessage {
-    pub channel: Channel,
+    // not needed for "time_sync"
+    pub channel: Option<Channel>,
     pub action: String,
+    #[serde(default)]
+    pub client_time: Option<f64>,
 }
 
 // message to be sent to websocket connections
@@ -265,6 +270,7 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WebSocketSession
             }
             ws::Message::Pong(_msg) => (),
             ws::Message::Text(msg) => {
+                let received = Utc::now().timestamp_millis();
                 let msg: WSClientRequestMessage = match serde_json::from_str(&msg) {
                     Ok(msg) => msg,
                     Err(_err) => {
@@ -277,12 +283,22 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WebSocketSession
                 };
                 debug!(LOG, "Dispatching message from WebSocket: {:?}", msg);
                 if msg.action == "subscribe" {
-                    if let Some(id) = self.id {
-                        self.server_addr.do_send(Subscribe {
-                            id,
-                            channel: msg.channel,
-                        });
+                    match (self.id, msg.channel) {
+                        (Some(id), Some(channel)) => {
+                            self.server_addr.do_send(Subscribe { id, channel })
+                        }
+                        (_, None) => warn!(LOG, "No channel to subscribe to given"),
+                        _ => (),
                     }
+                } else if msg.action == "time_sync" {
+                    // answered directly to the client, not via a channel
+                    ctx.text(
+                        json!({
+                            "action": "time_sync",
+                            "message": TimeSync::new(msg.client_time, received),
+                        })
+                        .to_string(),
+                    );
                 } else {
                     warn!(LOG, "Unknown action: '{}'", msg.action);
                 }